@@ -25,6 +25,23 @@ fn main() {
         ),
     }
 
+    match std::process::Command::new("git")
+        .args(&["rev-parse", "--short=7", "HEAD"])
+        .current_dir(&manifest_dir)
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            if let Err(err) = std::fs::write(install_dir.join("commit-hash"), output.stdout) {
+                println!("cargo:warning=failed to write current commit hash: {}", err)
+            }
+        }
+        Ok(output) => println!(
+            "cargo:warning=failed to get current commit hash: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(err) => println!("cargo:warning=failed to get current commit hash: {}", err),
+    }
+
     // Copy templates
     let bike = bicycle::Bicycle::default();
     for rel in ["platforms", "apps"]
@@ -1,16 +1,19 @@
 use crate::{
     config::app::App,
-    util::{self, cli::Report},
+    util::{self, cli::Report, VersionTriple, VersionTripleError},
 };
 use serde::{Deserialize, Serialize};
 use std::{
     fmt::{self, Display},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 const DEFAULT_MIN_SDK_VERSION: u32 = 24;
 const DEFAULT_VULKAN_VALIDATION: bool = true;
 static DEFAULT_PROJECT_DIR: &str = "gen/android";
+/// The keystore password is read from this, so it never has to be written
+/// down in the config.
+pub static KEYSTORE_PASSWORD_VAR: &str = "ANDROID_KEYSTORE_PASSWORD";
 
 const fn default_true() -> bool {
     true
@@ -130,6 +133,8 @@ impl Display for ProjectDirInvalid {
 #[derive(Debug)]
 pub enum Error {
     ProjectDirInvalid(ProjectDirInvalid),
+    NdkMinInvalid(VersionTripleError),
+    NdkMaxInvalid(VersionTripleError),
 }
 
 impl Error {
@@ -139,10 +144,30 @@ impl Error {
                 msg,
                 format!("`{}.project-dir` invalid: {}", super::NAME, err),
             ),
+            Self::NdkMinInvalid(err) => {
+                Report::error(msg, format!("`{}.ndk.min` invalid: {}", super::NAME, err))
+            }
+            Self::NdkMaxInvalid(err) => {
+                Report::error(msg, format!("`{}.ndk.max` invalid: {}", super::NAME, err))
+            }
         }
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RawReleaseSigning {
+    keystore: String,
+    alias: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RawNdk {
+    min: Option<String>,
+    max: Option<String>,
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Raw {
@@ -151,6 +176,93 @@ pub struct Raw {
     project_dir: Option<String>,
     no_default_features: Option<bool>,
     features: Option<Vec<String>>,
+    release_signing: Option<RawReleaseSigning>,
+    ndk: Option<RawNdk>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReleaseSigning {
+    keystore: PathBuf,
+    alias: String,
+}
+
+impl ReleaseSigning {
+    pub fn keystore(&self) -> &Path {
+        &self.keystore
+    }
+
+    pub fn alias(&self) -> &str {
+        &self.alias
+    }
+}
+
+/// One end of an [`NdkRange`]. Versions are only compared as far as the bound
+/// goes, so a `max` of `25.2` still allows `25.2.9519653`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(transparent)]
+pub struct NdkBound {
+    raw: String,
+    #[serde(skip)]
+    version: VersionTriple,
+}
+
+impl Display for NdkBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl NdkBound {
+    fn parse(raw: String) -> Result<Self, VersionTripleError> {
+        let version = VersionTriple::from_str(&raw)?;
+        Ok(Self { raw, version })
+    }
+
+    // Drops whatever `version` has past the components the bound has.
+    fn truncate(&self, version: VersionTriple) -> VersionTriple {
+        match self.raw.split('.').count() {
+            1 => VersionTriple::new(version.major, 0, 0),
+            2 => VersionTriple::new(version.major, version.minor, 0),
+            _ => version,
+        }
+    }
+
+    pub fn is_newer_than(&self, version: VersionTriple) -> bool {
+        self.truncate(version) < self.version
+    }
+
+    pub fn is_older_than(&self, version: VersionTriple) -> bool {
+        self.truncate(version) > self.version
+    }
+
+    /// Whether the bound is a whole version, and so names an exact `sdkmanager`
+    /// package.
+    pub fn is_full(&self) -> bool {
+        self.raw.split('.').count() >= 3
+    }
+}
+
+/// The NDK versions a project builds with, as `sdkmanager` names them (i.e.
+/// `25.2.9519653`). Both ends are inclusive.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct NdkRange {
+    min: Option<NdkBound>,
+    max: Option<NdkBound>,
+}
+
+impl NdkRange {
+    pub fn min(&self) -> Option<&NdkBound> {
+        self.min.as_ref()
+    }
+
+    pub fn max(&self) -> Option<&NdkBound> {
+        self.max.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.is_none() && self.max.is_none()
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -161,6 +273,8 @@ pub struct Config {
     min_sdk_version: u32,
     vulkan_validation: bool,
     project_dir: PathBuf,
+    release_signing: Option<ReleaseSigning>,
+    ndk: NdkRange,
 }
 
 impl Config {
@@ -222,11 +336,32 @@ impl Config {
             Ok(DEFAULT_PROJECT_DIR.into())
         }?;
 
+        let release_signing = raw.release_signing.map(|raw| ReleaseSigning {
+            keystore: app.prefix_path(raw.keystore),
+            alias: raw.alias,
+        });
+
+        let raw_ndk = raw.ndk.unwrap_or_default();
+        let ndk = NdkRange {
+            min: raw_ndk
+                .min
+                .map(NdkBound::parse)
+                .transpose()
+                .map_err(Error::NdkMinInvalid)?,
+            max: raw_ndk
+                .max
+                .map(NdkBound::parse)
+                .transpose()
+                .map_err(Error::NdkMaxInvalid)?,
+        };
+
         Ok(Self {
             app,
             min_sdk_version,
             vulkan_validation,
             project_dir,
+            release_signing,
+            ndk,
         })
     }
 
@@ -248,7 +383,41 @@ impl Config {
             .join(self.app().name())
     }
 
+    /// The keystore to sign release builds with, if there is one; without it,
+    /// release builds are left unsigned.
+    pub fn release_signing(&self) -> Option<&ReleaseSigning> {
+        self.release_signing.as_ref()
+    }
+
+    pub fn ndk(&self) -> &NdkRange {
+        &self.ndk
+    }
+
     pub fn project_dir_exists(&self) -> bool {
         self.project_dir().is_dir()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        bound,
+        version,
+        newer,
+        older,
+        full,
+        case("25.2", VersionTriple::new(25, 2, 9519653), false, false, false),
+        case("25.2", VersionTriple::new(25, 1, 8937393), true, false, false),
+        case("25", VersionTriple::new(26, 1, 10909125), false, true, false),
+        case("25.2.9519653", VersionTriple::new(25, 2, 9519652), true, false, true)
+    )]
+    fn test_ndk_bound(bound: &str, version: VersionTriple, newer: bool, older: bool, full: bool) {
+        let bound = NdkBound::parse(bound.to_owned()).unwrap();
+        assert_eq!(bound.is_newer_than(version), newer);
+        assert_eq!(bound.is_older_than(version), older);
+        assert_eq!(bound.is_full(), full);
+    }
+}
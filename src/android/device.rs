@@ -159,6 +159,14 @@ impl<'a> Device<'a> {
         }
     }
 
+    pub fn serial_no(&self) -> &str {
+        &self.serial_no
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn target(&self) -> &'a Target<'a> {
         self.target
     }
@@ -14,7 +14,7 @@ pub enum Error {
     #[error(transparent)]
     CoreEnvError(#[from] CoreError),
     // TODO: we should be nice and provide a platform-specific suggestion
-    #[error("Have you installed the Android SDK? The `ANDROID_SDK_ROOT` environment variable isn't set, and is required: {0}")]
+    #[error("Have you installed the Android SDK? Neither the `ANDROID_SDK_ROOT` nor the `ANDROID_HOME` environment variable is set, and one of them is required: {0}")]
     AndroidSdkRootNotSet(#[from] std::env::VarError),
     #[error("Have you installed the Android SDK? The `ANDROID_SDK_ROOT` environment variable is set, but doesn't point to an existing directory.")]
     AndroidSdkRootNotADir,
@@ -61,18 +61,6 @@ impl Env {
                     Err(Error::AndroidSdkRootNotADir)
                 }
             })
-            .or_else(|err| {
-                if let Some(android_home) = std::env::var("ANDROID_HOME")
-                    .ok()
-                    .map(PathBuf::from)
-                    .filter(|android_home| android_home.is_dir())
-                {
-                    log::warn!("`ANDROID_SDK_ROOT` isn't set; falling back to `ANDROID_HOME`, which is deprecated");
-                    Ok(android_home)
-                } else {
-                    Err(err)
-                }
-            })
             .or_else(|err| {
                 if let Some(android_home) = std::env::var("ANDROID_HOME")
                     .ok()
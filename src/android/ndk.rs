@@ -10,6 +10,7 @@ use once_cell_regex::regex_multi_line;
 use std::{
     collections::HashSet,
     fmt::{self, Display},
+    io,
     path::{Path, PathBuf},
 };
 use thiserror::Error;
@@ -165,6 +166,37 @@ impl Reportable for RequiredLibsError {
     }
 }
 
+fn version_at(ndk_home: &Path) -> Result<source_props::Revision, source_props::Error> {
+    SourceProps::from_path(ndk_home.join("source.properties")).map(|props| props.pkg.revision)
+}
+
+#[derive(Debug)]
+pub struct InstalledNdk {
+    pub path: PathBuf,
+    pub version: Result<source_props::Revision, source_props::Error>,
+}
+
+/// Lists the NDKs installed side-by-side in the SDK's `ndk` dir, which is
+/// where `sdkmanager` and Android Studio put them.
+pub fn find_installed(sdk_root: &Path) -> io::Result<Vec<InstalledNdk>> {
+    let ndk_dir = sdk_root.join("ndk");
+    if !ndk_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths = std::fs::read_dir(&ndk_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.retain(|path| path.is_dir());
+    paths.sort();
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let version = version_at(&path);
+            InstalledNdk { path, version }
+        })
+        .collect())
+}
+
 #[derive(Debug)]
 pub struct Env {
     ndk_home: PathBuf,
@@ -202,8 +234,7 @@ impl Env {
     }
 
     pub fn version(&self) -> Result<source_props::Revision, source_props::Error> {
-        SourceProps::from_path(self.ndk_home.join("source.properties"))
-            .map(|props| props.pkg.revision)
+        version_at(&self.ndk_home)
     }
 
     pub fn prebuilt_dir(&self) -> Result<PathBuf, MissingToolError> {
@@ -50,6 +50,14 @@ pub struct BuildScript {
     discovered_dependency_file: Option<String>,
 }
 
+impl BuildScript {
+    /// The script file to run, relative to the Xcode project, if it isn't
+    /// inline.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Platform {
@@ -327,6 +335,8 @@ pub struct Config {
     use_legacy_build_system: bool,
     plist_pairs: Vec<PListPair>,
     enable_bitcode: bool,
+    #[serde(skip_serializing)]
+    simulator: Option<String>,
 }
 
 impl Config {
@@ -398,6 +408,7 @@ impl Config {
             use_legacy_build_system: raw.use_legacy_build_system.unwrap_or(true),
             plist_pairs: raw.plist_pairs.unwrap_or_default(),
             enable_bitcode: raw.enable_bitcode.unwrap_or(false),
+            simulator: raw.simulator,
         })
     }
 
@@ -405,6 +416,15 @@ impl Config {
         &self.app
     }
 
+    /// The simulator to run on by default, by name or UDID.
+    pub fn simulator(&self) -> Option<&str> {
+        self.simulator.as_deref()
+    }
+
+    pub fn ios_version(&self) -> VersionDouble {
+        self.ios_version
+    }
+
     pub fn project_dir(&self) -> PathBuf {
         self.app.prefix_path(&self.project_dir)
     }
@@ -133,6 +133,7 @@ pub struct Raw {
     pub use_legacy_build_system: Option<bool>,
     pub plist_pairs: Option<Vec<PListPair>>,
     pub enable_bitcode: Option<bool>,
+    pub simulator: Option<String>,
 }
 
 impl Raw {
@@ -156,6 +157,7 @@ impl Raw {
             use_legacy_build_system: None,
             plist_pairs: None,
             enable_bitcode: None,
+            simulator: None,
         })
     }
 
@@ -238,6 +240,7 @@ impl Raw {
             use_legacy_build_system: None,
             plist_pairs: None,
             enable_bitcode: None,
+            simulator: None,
         })
     }
 }
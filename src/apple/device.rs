@@ -44,6 +44,7 @@ pub struct Device<'a> {
     id: String,
     name: String,
     model: String,
+    os_version: Option<String>,
     target: &'a Target<'a>,
 }
 
@@ -54,15 +55,34 @@ impl<'a> Display for Device<'a> {
 }
 
 impl<'a> Device<'a> {
-    pub(super) fn new(id: String, name: String, model: String, target: &'a Target<'a>) -> Self {
+    pub(super) fn new(
+        id: String,
+        name: String,
+        model: String,
+        os_version: Option<String>,
+        target: &'a Target<'a>,
+    ) -> Self {
         Self {
             id,
             name,
             model,
+            os_version,
             target,
         }
     }
 
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn os_version(&self) -> Option<&str> {
+        self.os_version.as_deref()
+    }
+
     pub fn target(&self) -> &'a Target<'a> {
         self.target
     }
@@ -22,7 +22,13 @@ impl Reportable for DeviceListError {
 }
 
 fn parse_device_list<'a>(output: &bossy::Output) -> Result<BTreeSet<Device<'a>>, DeviceListError> {
-    Event::parse_list(output.stdout_str()?)
+    parse_detected_devices(output.stdout_str()?)
+}
+
+/// Parses the stdout of `ios-deploy --detect --json`, for callers that need
+/// to run it themselves.
+pub fn parse_detected_devices<'a>(stdout: &str) -> Result<BTreeSet<Device<'a>>, DeviceListError> {
+    Event::parse_list(stdout)
         .into_iter()
         .flat_map(|event| event.device_info().cloned())
         .map(
@@ -31,9 +37,18 @@ fn parse_device_list<'a>(output: &bossy::Output) -> Result<BTreeSet<Device<'a>>,
                  device_name,
                  model_arch,
                  model_name,
+                 product_version,
              }| {
                 Target::for_arch(&model_arch)
-                    .map(|target| Device::new(device_identifier, device_name, model_name, target))
+                    .map(|target| {
+                        Device::new(
+                            device_identifier,
+                            device_name,
+                            model_name,
+                            product_version,
+                            target,
+                        )
+                    })
                     .ok_or_else(|| DeviceListError::ArchInvalid(model_arch))
             },
         )
@@ -16,6 +16,8 @@ struct DeviceInfo {
     model_arch: String,
     #[serde(rename = "modelName")]
     model_name: String,
+    #[serde(rename = "ProductVersion", default)]
+    product_version: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
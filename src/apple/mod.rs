@@ -1,21 +1,31 @@
 pub mod cli;
 pub(crate) mod config;
 pub(crate) mod deps;
-mod device;
+pub(crate) mod device;
 pub(crate) mod ios_deploy;
 pub(crate) mod project;
 pub(crate) mod system_profile;
-mod target;
+pub(crate) mod target;
 pub(crate) mod teams;
 mod version_number;
 
 use crate::util::{
     self,
     cli::{Report, TextWrapper},
+    VersionDouble,
 };
 
 pub static NAME: &str = "apple";
 
+/// Detects the version of the installed Xcode, so that callers can bail early
+/// when it's too old for what they're about to do.
+pub fn xcode_version() -> Result<VersionDouble, system_profile::Error> {
+    system_profile::DeveloperTools::new().map(|dev_tools| {
+        let (major, minor) = dev_tools.version;
+        VersionDouble::new(major, minor)
+    })
+}
+
 pub fn rust_version_check(wrapper: &TextWrapper) -> Result<(), util::RustVersionError> {
     util::RustVersion::check().map(|version| if !version.valid() {
         Report::action_request(
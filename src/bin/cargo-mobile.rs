@@ -10,7 +10,7 @@ use cargo_mobile::{
     },
     NAME,
 };
-use std::path::PathBuf;
+use std::{io::IsTerminal as _, path::PathBuf};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -95,7 +95,195 @@ pub enum Command {
         name = "doctor",
         about = "Perform a check-up on your installation and environment"
     )]
-    Doctor,
+    Doctor {
+        #[structopt(
+            long = "format",
+            help = "Output format for the report",
+            default_value = "text",
+            possible_values = &doctor::Format::variants(),
+            case_insensitive = true,
+        )]
+        format: doctor::Format,
+        #[structopt(
+            long = "fail-on",
+            help = "Exit with a non-zero status when a check reaches this severity",
+            default_value = "warning",
+            possible_values = &doctor::FailOn::variants(),
+            case_insensitive = true,
+        )]
+        fail_on: doctor::FailOn,
+        #[structopt(
+            long = "only",
+            help = "Only check this section (cargo-mobile, apple, android, or device-list)"
+        )]
+        only: Option<doctor::SectionId>,
+        #[structopt(
+            long = "section-order",
+            help = "Comma-separated sections to print first; the rest follow in the default order",
+            use_delimiter = true,
+            conflicts_with = "only"
+        )]
+        section_order: Vec<String>,
+        #[structopt(
+            long = "target",
+            help = "Only check the Rust target and linker for this triple, along with its platform's section",
+            conflicts_with_all = &["only", "section-order"]
+        )]
+        target: Option<String>,
+        #[structopt(
+            long = "no-color",
+            help = "Disable colored output (also respects `NO_COLOR`)",
+            parse(from_flag = opts::NoColor::from_bool),
+        )]
+        no_color: opts::NoColor,
+        #[structopt(
+            long = "plain",
+            help = "Prefix each item with a stable severity token like `[FAIL]`",
+            parse(from_flag = opts::Plain::from_bool),
+        )]
+        plain: opts::Plain,
+        #[structopt(
+            long = "ascii",
+            help = "Use ASCII symbols, for terminals that can't render unicode (implied by non-UTF-8 locales)",
+            parse(from_flag = opts::Ascii::from_bool),
+        )]
+        ascii: opts::Ascii,
+        #[structopt(
+            long = "problems-only",
+            help = "Only print warnings and errors",
+            parse(from_flag = opts::ProblemsOnly::from_bool),
+        )]
+        problems_only: opts::ProblemsOnly,
+        #[structopt(
+            long = "sort-severity",
+            help = "List each section's errors first, then warnings, then everything else",
+            parse(from_flag = opts::SortSeverity::from_bool),
+        )]
+        sort_severity: opts::SortSeverity,
+        #[structopt(
+            long = "include-slow",
+            help = "Also run checks that are slow, i.e. because they hit the network",
+            parse(from_flag = opts::IncludeSlow::from_bool),
+        )]
+        include_slow: opts::IncludeSlow,
+        #[structopt(
+            long = "skip",
+            help = "Leave out the check with this ID, i.e. `android.java` (can be repeated)",
+            number_of_values = 1
+        )]
+        skip: Vec<String>,
+        #[structopt(
+            long = "omit-skipped",
+            help = "Leave skipped checks out of the report entirely",
+            parse(from_flag = opts::OmitSkipped::from_bool),
+        )]
+        omit_skipped: opts::OmitSkipped,
+        #[structopt(
+            long = "no-device-checks",
+            help = "Don't check for connected devices (the default when `CI` is set)"
+        )]
+        no_device_checks: bool,
+        #[structopt(
+            long = "device-checks",
+            help = "Check for connected devices even when `CI` is set",
+            conflicts_with = "no-device-checks"
+        )]
+        device_checks: bool,
+        #[structopt(
+            long = "timeout",
+            help = "Seconds to wait on commands that tend to hang, i.e. device enumeration",
+            default_value = "10"
+        )]
+        timeout: u64,
+        #[structopt(
+            long = "fix",
+            help = "Offer to fix the problems that can be fixed automatically (`-y` skips the prompts)",
+            parse(from_flag = opts::Fix::from_bool),
+        )]
+        fix: opts::Fix,
+        #[structopt(
+            long = "width",
+            help = "Wrap output at this many columns (defaults to `COLUMNS` or the terminal width, and to no wrapping when piped)"
+        )]
+        width: Option<usize>,
+        #[structopt(
+            long = "watch",
+            help = "Re-run the checks on an interval, redrawing the report when it changes (Ctrl-C to stop)",
+            parse(from_flag = opts::Watch::from_bool),
+            conflicts_with_all = &["fix", "output", "since", "save"],
+        )]
+        watch: opts::Watch,
+        #[structopt(
+            long = "watch-interval",
+            help = "Seconds to wait between runs in `--watch` mode",
+            default_value = "3"
+        )]
+        watch_interval: u64,
+        #[structopt(
+            long = "output",
+            help = "Write the report to this file instead of stdout"
+        )]
+        output: Option<PathBuf>,
+        #[structopt(
+            long = "force-color",
+            help = "Keep the colors when writing the report to a file",
+            parse(from_flag = opts::ForceColor::from_bool),
+        )]
+        force_color: opts::ForceColor,
+        #[structopt(
+            long = "since",
+            help = "Compare against a report saved with `--save`, highlighting checks that started or stopped failing"
+        )]
+        since: Option<PathBuf>,
+        #[structopt(
+            long = "save",
+            help = "Save the report to this file as JSON, for use with `--since` later"
+        )]
+        save: Option<PathBuf>,
+        #[structopt(
+            long = "first-error",
+            help = "Stop at the first error and only print that, for quick pass/fail checks",
+            parse(from_flag = opts::FirstError::from_bool),
+        )]
+        first_error: opts::FirstError,
+        #[structopt(
+            long = "status",
+            help = "Only print `mobile:ok`, `mobile:warn`, or `mobile:err`, exiting with 0, 2, or 1 respectively (slow checks are never run)",
+            conflicts_with_all = &["fix", "watch", "output", "issue"]
+        )]
+        status: bool,
+        #[structopt(
+            long = "health",
+            help = "Only print the worst label, the counts, and each section's label, as JSON",
+            conflicts_with_all = &["fix", "watch", "output", "status", "issue"]
+        )]
+        health: bool,
+        #[structopt(
+            long = "issue",
+            help = "Only print a plain summary of the environment and any problems, for pasting into a bug report",
+            conflicts_with_all = &["fix", "watch", "output"]
+        )]
+        issue: bool,
+        #[structopt(
+            long = "explain",
+            help = "Only print what the check with this ID does and how to fix it, i.e. `android.java`",
+            value_name = "check-id",
+            conflicts_with_all = &["only", "devices-json", "missing-targets"]
+        )]
+        explain: Option<String>,
+        #[structopt(
+            long = "missing-targets",
+            help = "Only print the Rust targets that need to be installed, one per line",
+            conflicts_with_all = &["only", "devices-json"]
+        )]
+        missing_targets: bool,
+        #[structopt(
+            long = "devices-json",
+            help = "Only print the connected devices, as JSON",
+            conflicts_with = "only"
+        )]
+        devices_json: bool,
+    },
 }
 
 #[derive(Debug)]
@@ -115,6 +303,9 @@ pub enum Error {
     AppleFailed(cargo_mobile::apple::cli::Error),
     AndroidFailed(cargo_mobile::android::cli::Error),
     DoctorFailed(doctor::Unrecoverable),
+    ListTargetsFailed(doctor::ListTargetsFailed),
+    ExplainFailed(doctor::UnknownCheck),
+    TargetInvalid(doctor::TargetInvalid),
 }
 
 impl Reportable for Error {
@@ -136,6 +327,9 @@ impl Reportable for Error {
             Self::AppleFailed(err) => err.report(),
             Self::AndroidFailed(err) => err.report(),
             Self::DoctorFailed(err) => Report::error("Failed to run doctor", err),
+            Self::ListTargetsFailed(err) => Report::error("Failed to check Rust targets", err),
+            Self::ExplainFailed(err) => Report::error("Failed to explain check", err),
+            Self::TargetInvalid(err) => Report::error("Invalid target", err),
         }
     }
 }
@@ -150,7 +344,8 @@ impl Exec for Input {
     fn exec(self, wrapper: &TextWrapper) -> Result<(), Self::Report> {
         let Self { flags, command } = self;
         let GlobalFlags {
-            non_interactive, ..
+            noise_level,
+            non_interactive,
         } = flags;
         match command {
             Command::Init {
@@ -220,7 +415,141 @@ impl Exec for Input {
             Command::Android(command) => cargo_mobile::android::cli::Input::new(flags, command)
                 .exec(wrapper)
                 .map_err(Error::AndroidFailed),
-            Command::Doctor => doctor::exec(wrapper).map_err(Error::DoctorFailed),
+            Command::Doctor {
+                explain: Some(id), ..
+            } => doctor::exec_explain(wrapper, &id).map_err(Error::ExplainFailed),
+            Command::Doctor { devices_json, .. } if devices_json => {
+                doctor::exec_devices().map_err(Error::DoctorFailed)
+            }
+            Command::Doctor {
+                missing_targets, ..
+            } if missing_targets => {
+                let missing = doctor::missing_rust_targets().map_err(Error::ListTargetsFailed)?;
+                for triple in &missing {
+                    println!("{}", triple);
+                }
+                if missing.is_empty() {
+                    Ok(())
+                } else {
+                    std::process::exit(1)
+                }
+            }
+            Command::Doctor {
+                format,
+                fail_on,
+                only,
+                section_order,
+                target,
+                no_color,
+                plain,
+                ascii,
+                problems_only,
+                sort_severity,
+                include_slow,
+                skip,
+                omit_skipped,
+                no_device_checks,
+                device_checks,
+                timeout,
+                fix,
+                width,
+                watch,
+                watch_interval,
+                output,
+                force_color,
+                since,
+                save,
+                issue,
+                health,
+                status,
+                first_error,
+                ..
+            } => {
+                let mut wrapper = wrapper.clone();
+                if let Some(width) = width {
+                    wrapper.width = width;
+                } else if output.is_some() || !std::io::stdout().is_terminal() {
+                    // Whatever ends up showing the report (i.e. a pager) has its
+                    // own idea of the width, so we leave the wrapping to it.
+                    wrapper.width = usize::MAX;
+                }
+                let print_opts = doctor::PrintOpts {
+                    no_color,
+                    plain,
+                    problems_only,
+                    sort_severity,
+                    symbols: doctor::Symbols::detect(ascii),
+                    noise_level,
+                };
+                let ids = match &target {
+                    Some(triple) => vec![
+                        doctor::SectionId::CargoMobile,
+                        doctor::SectionId::for_target(triple).map_err(Error::TargetInvalid)?,
+                    ],
+                    None => only
+                        .map(|id| vec![id])
+                        .unwrap_or_else(|| doctor::section_order(&section_order)),
+                };
+                let check_opts = doctor::CheckOpts {
+                    noise_level,
+                    include_slow,
+                    skip,
+                    omit_skipped,
+                    timeout: std::time::Duration::from_secs(timeout),
+                    first_error,
+                    target,
+                    no_device_checks: opts::NoDeviceChecks::from_bool(
+                        no_device_checks
+                            || (!device_checks
+                                && doctor::CheckOpts::device_checks_skipped_by_default()),
+                    ),
+                };
+                if status {
+                    let worst =
+                        doctor::exec_status(&check_opts, &ids).map_err(Error::DoctorFailed)?;
+                    // Warnings always get their own code here, since telling
+                    // them apart is the whole point.
+                    match doctor::FailOn::Warning.exit_code(worst) {
+                        0 => return Ok(()),
+                        code => std::process::exit(code),
+                    }
+                }
+                if health {
+                    return doctor::exec_health(&check_opts, &ids).map_err(Error::DoctorFailed);
+                }
+                if issue {
+                    return doctor::exec_issue(&check_opts, &ids).map_err(Error::DoctorFailed);
+                }
+                if watch.yes() {
+                    return doctor::watch(
+                        &wrapper,
+                        &check_opts,
+                        format,
+                        &ids,
+                        print_opts,
+                        std::time::Duration::from_secs(watch_interval),
+                    )
+                    .map_err(Error::DoctorFailed);
+                }
+                let worst = doctor::exec(
+                    &wrapper,
+                    &check_opts,
+                    non_interactive,
+                    format,
+                    &ids,
+                    print_opts,
+                    fix,
+                    output.as_deref(),
+                    force_color,
+                    since.as_deref(),
+                    save.as_deref(),
+                )
+                .map_err(Error::DoctorFailed)?;
+                match fail_on.exit_code(worst) {
+                    0 => Ok(()),
+                    code => std::process::exit(code),
+                }
+            }
         }
     }
 }
@@ -3,7 +3,7 @@ use std::error::Error;
 use std::fmt;
 
 static RESERVED_PACKAGE_NAMES: [&str; 2] = ["kotlin", "java"];
-static RESERVED_KEYWORDS: [&str; 63] = [
+pub(crate) static RESERVED_KEYWORDS: [&str; 63] = [
     "abstract",
     "as",
     "assert",
@@ -6,18 +6,29 @@ use self::{app::App, raw::*};
 #[cfg(target_os = "macos")]
 use crate::apple;
 use crate::{
-    android,
+    android, doctor,
     opts::NonInteractive,
     templating,
-    util::cli::{Report, Reportable, TextWrapper},
+    util::{
+        self,
+        cli::{Report, Reportable, TextWrapper},
+    },
 };
 use serde::Serialize;
 use std::{
     fmt::Debug,
-    io,
+    fs, io,
     path::{Path, PathBuf},
 };
 
+static GEN_HASH_FILE_NAME: &str = ".config-hash";
+static TEMPLATE_VERSION_FILE_NAME: &str = ".template-version";
+
+/// The version of the project templates this build generates. Bump this
+/// whenever the templates change in a way that projects generated from older
+/// ones won't build with.
+pub const TEMPLATE_VERSION: u32 = 1;
+
 pub fn file_name() -> String {
     format!("{}.toml", crate::NAME)
 }
@@ -66,13 +77,12 @@ impl Reportable for GenError {
 }
 
 #[derive(Debug)]
-pub enum LoadOrGenError {
+pub enum LoadExistingError {
     LoadFailed(LoadError),
     FromRawFailed { path: PathBuf, cause: FromRawError },
-    GenFailed(GenError),
 }
 
-impl Reportable for LoadOrGenError {
+impl Reportable for LoadExistingError {
     fn report(&self) -> Report {
         match self {
             Self::LoadFailed(err) => Report::error("Failed to load config", err),
@@ -80,6 +90,20 @@ impl Reportable for LoadOrGenError {
                 let msg = format!("Config file at {:?} invalid", path);
                 cause.report(&msg)
             }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadOrGenError {
+    LoadFailed(LoadExistingError),
+    GenFailed(GenError),
+}
+
+impl Reportable for LoadOrGenError {
+    fn report(&self) -> Report {
+        match self {
+            Self::LoadFailed(err) => err.report(),
             Self::GenFailed(err) => err.report(),
         }
     }
@@ -104,6 +128,8 @@ pub struct Config {
     #[cfg(target_os = "macos")]
     apple: apple::config::Config,
     android: android::config::Config,
+    #[serde(skip)]
+    doctor: doctor::config::Config,
     env: Option<toml::value::Table>,
 }
 
@@ -120,6 +146,7 @@ impl Config {
             #[cfg(target_os = "macos")]
             apple,
             android,
+            doctor: doctor::config::Config::from_raw(raw.doctor),
             env: raw.env,
         })
     }
@@ -145,19 +172,30 @@ impl Config {
         Ok(config)
     }
 
+    /// Like `load_or_gen`, but gives you `None` instead of generating a config
+    /// if there isn't one.
+    pub fn load(cwd: impl AsRef<Path>) -> Result<Option<Self>, LoadExistingError> {
+        Raw::load(cwd)
+            .map_err(LoadExistingError::LoadFailed)?
+            .map(|(root_dir, raw)| {
+                Self::from_raw(root_dir.clone(), raw).map_err(|cause| {
+                    LoadExistingError::FromRawFailed {
+                        path: root_dir,
+                        cause,
+                    }
+                })
+            })
+            .transpose()
+    }
+
     pub fn load_or_gen(
         cwd: impl AsRef<Path>,
         non_interactive: NonInteractive,
         wrapper: &TextWrapper,
     ) -> Result<(Self, Origin), LoadOrGenError> {
         let cwd = cwd.as_ref();
-        if let Some((root_dir, raw)) = Raw::load(cwd).map_err(LoadOrGenError::LoadFailed)? {
-            Self::from_raw(root_dir.clone(), raw)
-                .map(|config| (config, Origin::Loaded))
-                .map_err(|cause| LoadOrGenError::FromRawFailed {
-                    path: root_dir,
-                    cause,
-                })
+        if let Some(config) = Self::load(cwd).map_err(LoadOrGenError::LoadFailed)? {
+            Ok((config, Origin::Loaded))
         } else {
             Self::gen(cwd, non_interactive, wrapper)
                 .map(|config| (config, Origin::FreshlyMinted))
@@ -169,6 +207,41 @@ impl Config {
         self.app().root_dir().join(file_name())
     }
 
+    /// A hash of the config file's contents, which is recorded in generated
+    /// projects so that we can tell when they're stale.
+    pub fn contents_hash(&self) -> io::Result<String> {
+        fs::read(self.path()).map(|bytes| format!("{:016x}", util::stable_hash(&bytes)))
+    }
+
+    pub fn gen_hash_path(project_dir: &Path) -> PathBuf {
+        project_dir.join(GEN_HASH_FILE_NAME)
+    }
+
+    /// Records the config's current hash in a generated project.
+    pub fn write_gen_hash(&self, project_dir: &Path) -> io::Result<()> {
+        fs::write(Self::gen_hash_path(project_dir), self.contents_hash()?)
+    }
+
+    pub fn template_version_path(project_dir: &Path) -> PathBuf {
+        project_dir.join(TEMPLATE_VERSION_FILE_NAME)
+    }
+
+    /// The template version a project was generated with, if it was recorded.
+    /// Projects generated before we started recording it don't have one.
+    pub fn read_template_version(project_dir: &Path) -> Option<u32> {
+        fs::read_to_string(Self::template_version_path(project_dir))
+            .ok()
+            .and_then(|version| version.trim().parse().ok())
+    }
+
+    /// Records the current template version in a generated project.
+    pub fn write_template_version(project_dir: &Path) -> io::Result<()> {
+        fs::write(
+            Self::template_version_path(project_dir),
+            TEMPLATE_VERSION.to_string(),
+        )
+    }
+
     pub fn app(&self) -> &App {
         &self.app
     }
@@ -182,6 +255,10 @@ impl Config {
         &self.android
     }
 
+    pub fn doctor(&self) -> &doctor::config::Config {
+        &self.doctor
+    }
+
     pub fn env(&self) -> &Option<toml::value::Table> {
         &self.env
     }
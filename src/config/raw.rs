@@ -2,7 +2,7 @@ use super::app;
 #[cfg(target_os = "macos")]
 use crate::apple;
 use crate::{
-    android,
+    android, doctor,
     util::cli::{Report, Reportable, TextWrapper},
 };
 use serde::{Deserialize, Serialize};
@@ -109,6 +109,7 @@ pub struct Raw {
     #[cfg(target_os = "macos")]
     pub apple: Option<apple::config::Raw>,
     pub android: Option<android::config::Raw>,
+    pub doctor: Option<doctor::config::Raw>,
     pub env: Option<toml::value::Table>,
 }
 
@@ -122,6 +123,7 @@ impl Raw {
             #[cfg(target_os = "macos")]
             apple: Some(apple),
             android: None,
+            doctor: None,
             env: None,
         })
     }
@@ -135,6 +137,7 @@ impl Raw {
             #[cfg(target_os = "macos")]
             apple: Some(apple),
             android: None,
+            doctor: None,
             env: None,
         })
     }
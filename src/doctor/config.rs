@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// DerivedData past this is mostly leftovers from old projects and Xcode
+// versions.
+const DEFAULT_DERIVED_DATA_LIMIT_GB: u64 = 20;
+
+/// Tool versions that everyone on the team should have. Versions only need to
+/// match as far as they go, so `15` matches any Xcode 15.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Expected {
+    xcode: Option<String>,
+    ndk: Option<String>,
+    rust: Option<String>,
+}
+
+impl Expected {
+    pub fn xcode(&self) -> Option<&str> {
+        self.xcode.as_deref()
+    }
+
+    pub fn ndk(&self) -> Option<&str> {
+        self.ndk.as_deref()
+    }
+
+    pub fn rust(&self) -> Option<&str> {
+        self.rust.as_deref()
+    }
+}
+
+/// System packages that builds need, per package manager. Only the one for the
+/// current platform is checked.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RequiredPackages {
+    brew: Option<Vec<String>>,
+    apt: Option<Vec<String>>,
+}
+
+impl RequiredPackages {
+    pub fn brew(&self) -> &[String] {
+        self.brew.as_deref().unwrap_or_default()
+    }
+
+    pub fn apt(&self) -> &[String] {
+        self.apt.as_deref().unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Raw {
+    pub skip: Option<Vec<String>>,
+    pub messages: Option<HashMap<String, String>>,
+    pub required_env: Option<Vec<String>>,
+    pub derived_data_limit_gb: Option<u64>,
+    pub expected: Option<Expected>,
+    pub required_packages: Option<RequiredPackages>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    skip: Vec<String>,
+    messages: HashMap<String, String>,
+    required_env: Vec<String>,
+    derived_data_limit_gb: u64,
+    expected: Expected,
+    required_packages: RequiredPackages,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::from_raw(None)
+    }
+}
+
+impl Config {
+    pub fn from_raw(raw: Option<Raw>) -> Self {
+        let raw = raw.unwrap_or_default();
+        Self {
+            skip: raw.skip.unwrap_or_default(),
+            messages: raw.messages.unwrap_or_default(),
+            required_env: raw.required_env.unwrap_or_default(),
+            derived_data_limit_gb: raw
+                .derived_data_limit_gb
+                .unwrap_or(DEFAULT_DERIVED_DATA_LIMIT_GB),
+            expected: raw.expected.unwrap_or_default(),
+            required_packages: raw.required_packages.unwrap_or_default(),
+        }
+    }
+
+    /// IDs of the checks to leave out of the report, i.e. `android.java`.
+    pub fn skip(&self) -> &[String] {
+        &self.skip
+    }
+
+    /// Replacement wording for doctor messages, keyed by message ID. Templates
+    /// use the same `{param}` placeholders as the messages they replace.
+    pub fn messages(&self) -> &HashMap<String, String> {
+        &self.messages
+    }
+
+    /// Names of environment variables that builds need, i.e. `NDK_HOME`.
+    pub fn required_env(&self) -> &[String] {
+        &self.required_env
+    }
+
+    /// How big Xcode's DerivedData can get before we suggest clearing it out.
+    pub fn derived_data_limit_gb(&self) -> u64 {
+        self.derived_data_limit_gb
+    }
+
+    pub fn expected(&self) -> &Expected {
+        &self.expected
+    }
+
+    pub fn required_packages(&self) -> &RequiredPackages {
+        &self.required_packages
+    }
+}
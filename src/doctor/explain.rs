@@ -0,0 +1,552 @@
+use crate::util::cli::TextWrapper;
+use colored::Colorize as _;
+use thiserror::Error;
+
+/// The long-form help for a single check, for `cargo mobile doctor --explain`.
+#[derive(Debug)]
+pub struct Explanation {
+    id: &'static str,
+    summary: &'static str,
+    command: Option<&'static str>,
+    why: &'static str,
+    fix: &'static str,
+}
+
+impl Explanation {
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    pub fn summary(&self) -> &'static str {
+        self.summary
+    }
+
+    pub fn command(&self) -> Option<&'static str> {
+        self.command
+    }
+
+    pub fn why(&self) -> &'static str {
+        self.why
+    }
+
+    pub fn fix(&self) -> &'static str {
+        self.fix
+    }
+
+    pub fn print(&self, wrapper: &TextWrapper) {
+        let indented = wrapper
+            .clone()
+            .initial_indent("    ")
+            .subsequent_indent("    ");
+        println!(
+            "{}",
+            wrapper.fill(&format!("{}: {}", self.id.bold(), self.summary))
+        );
+        if let Some(command) = self.command {
+            println!("\n{}", "Runs:".bold());
+            println!("{}", indented.fill(&format!("`{}`", command)));
+        }
+        println!("\n{}", "Why it matters:".bold());
+        println!("{}", indented.fill(self.why));
+        println!("\n{}", "How to fix it:".bold());
+        println!("{}", indented.fill(self.fix));
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("There's no check with the ID {id:?}; the possible IDs are {possible:?}")]
+pub struct UnknownCheck {
+    id: String,
+    possible: Vec<&'static str>,
+}
+
+macro_rules! explain {
+    ($id:literal, $summary:literal, $command:expr, $why:literal, $fix:literal $(,)?) => {
+        Explanation {
+            id: $id,
+            summary: $summary,
+            command: $command,
+            why: $why,
+            fix: $fix,
+        }
+    };
+}
+
+// Keyed by the same IDs as `--skip` and the JSON report. Checks that only run
+// in some environments are still listed, since the whole point is to explain
+// a check from a report that came from somewhere else.
+static EXPLANATIONS: &[Explanation] = &[
+    explain!(
+        "cargo-mobile.version",
+        "Which version of cargo-mobile is running.",
+        None,
+        "Behavior changes between versions, so this is the first thing anyone needs to know when helping with a problem.",
+        "Nothing to fix; run `cargo mobile update` if you want the latest version.",
+    ),
+    explain!(
+        "cargo-mobile.commit",
+        "Which commit cargo-mobile was installed from.",
+        None,
+        "Installs from git can be between releases, so the version alone doesn't say exactly what's running.",
+        "Nothing to fix; run `cargo mobile update` if you want the latest commit.",
+    ),
+    explain!(
+        "cargo-mobile.install-dir",
+        "Whether cargo-mobile's installation directory is still there.",
+        None,
+        "Templates and other support files are loaded from the installation directory, so project generation fails without it.",
+        "Reinstall cargo-mobile, or run `cargo mobile update`.",
+    ),
+    explain!(
+        "cargo-mobile.os",
+        "Which operating system and version you're on.",
+        None,
+        "Which tools are available and how they behave depends on the OS.",
+        "Nothing to fix; this is informational.",
+    ),
+    explain!(
+        "cargo-mobile.rust",
+        "Which Rust toolchain is active, and whether it's known to be broken.",
+        Some("rustc --version"),
+        "Some Rust releases have shipped with broken iOS linking, and builds fail in confusing ways on them.",
+        "Run `rustup update`, or switch to a toolchain that isn't flagged.",
+    ),
+    explain!(
+        "cargo-mobile.expected-rust",
+        "Whether the Rust version matches the one the project's doctor config expects.",
+        Some("rustc --version"),
+        "Teams pin tool versions so everyone's builds behave the same, and a mismatch is a common source of \"works on my machine\".",
+        "Install the expected version with `rustup`, or update `doctor.expected.rust` in the project config.",
+    ),
+    explain!(
+        "cargo-mobile.toolchain",
+        "Whether the active toolchain is the one pinned by `rust-toolchain` or `rust-toolchain.toml`.",
+        Some("rustc --version"),
+        "`RUSTUP_TOOLCHAIN` and `rustup override` take precedence over the pinned toolchain, so builds can quietly use a different compiler than the project asks for.",
+        "Unset `RUSTUP_TOOLCHAIN`, or remove the override with `rustup override unset`.",
+    ),
+    explain!(
+        "cargo-mobile.locale",
+        "Whether the locale is set to a UTF-8 one.",
+        None,
+        "Many tools only print UTF-8 when the locale says they can, and anything else can't be read by cargo-mobile.",
+        "Set `LANG` (or `LC_ALL`) to a UTF-8 locale, i.e. `en_US.UTF-8`.",
+    ),
+    explain!(
+        "cargo-mobile.shell",
+        "Which shell commands are run through.",
+        None,
+        "Quoting and PATH lookup differ between shells, which matters when reading failures from Windows.",
+        "Nothing to fix; this is informational.",
+    ),
+    explain!(
+        "cargo-mobile.config",
+        "Whether a project config was found in or above the current directory.",
+        None,
+        "Project-specific checks, i.e. for targets and signing, only run when there's a project to check.",
+        "Run the doctor from inside your project, or run `cargo mobile init` to create one.",
+    ),
+    explain!(
+        "cargo-mobile.targets",
+        "Whether the Rust targets that the project builds for are installed, leaving out unsupported platforms and iOS archs that aren't in `valid-archs`.",
+        Some("rustup target list --installed"),
+        "Building for a platform needs its Rust target, and a missing one only shows up once a build starts.",
+        "Run `rustup target add <triple>` for each missing target, or `cargo mobile init` to install them all.",
+    ),
+    explain!(
+        "cargo-mobile.gen-freshness",
+        "Whether the generated projects are up-to-date with the project config.",
+        None,
+        "Changes to the config only take effect once the projects are regenerated, so builds otherwise use stale settings.",
+        "Run `cargo mobile init` to regenerate the projects.",
+    ),
+    explain!(
+        "cargo-mobile.template-version",
+        "Whether the generated projects use the templates this version of cargo-mobile expects.",
+        None,
+        "Older templates can be missing settings that newer versions of cargo-mobile rely on.",
+        "Run `cargo mobile init` to regenerate the projects from the current templates.",
+    ),
+    explain!(
+        "cargo-mobile.required-env",
+        "Whether the environment variables listed in the project config are set.",
+        None,
+        "Build scripts and tools that read these fail in their own ways when they're missing.",
+        "Set each missing variable, or set it in the project config's `env` table.",
+    ),
+    explain!(
+        "cargo-mobile.path-tools",
+        "Whether more than one copy of each tool is on PATH.",
+        None,
+        "Only the first copy on PATH runs, so updating a different one looks like it did nothing.",
+        "Remove the extra copies, or reorder PATH so the one you want comes first.",
+    ),
+    explain!(
+        "cargo-mobile.mirrors",
+        "Whether crates.io and the other download hosts can be reached (slow; only with `--include-slow`).",
+        None,
+        "Proxies and firewalls are the usual reason dependency downloads fail, and the tools themselves are rarely clear about it.",
+        "Check your network connection, and set `HTTPS_PROXY` if you're behind a proxy.",
+    ),
+    explain!(
+        "cargo-mobile.packages",
+        "Whether the system packages listed in the project's doctor config are installed (slow; only with `--include-slow`).",
+        Some("brew list --versions <package>"),
+        "Minimal machines and CI images tend to be missing a library that some tool quietly depends on.",
+        "Install the missing packages with `brew` or `apt`.",
+    ),
+    explain!(
+        "apple.xcode",
+        "Which version of Xcode is selected.",
+        Some("xcodebuild -version"),
+        "Everything on the Apple side is built with Xcode, and older versions can't build for newer iOS releases.",
+        "Install Xcode from the App Store or https://developer.apple.com/download.",
+    ),
+    explain!(
+        "apple.expected-xcode",
+        "Whether the Xcode version matches the one the project's doctor config expects.",
+        Some("xcodebuild -version"),
+        "Teams pin tool versions so everyone's builds behave the same.",
+        "Install the expected version, or update `doctor.expected.xcode` in the project config.",
+    ),
+    explain!(
+        "apple.developer-dir",
+        "Whether the selected developer directory exists.",
+        Some("xcode-select -p"),
+        "Every Xcode tool goes through the selected developer directory, so they all fail if it's gone.",
+        "Run `sudo xcode-select --switch /Applications/Xcode.app`.",
+    ),
+    explain!(
+        "apple.full-xcode",
+        "Whether the full Xcode is selected, rather than just the command-line tools.",
+        Some("xcodebuild -version"),
+        "`cargo` can link with just the command-line tools, but simulators and archiving need the full Xcode.",
+        "Run `sudo xcode-select --switch /Applications/Xcode.app`.",
+    ),
+    explain!(
+        "apple.beta-xcode",
+        "Whether the selected Xcode is a beta.",
+        Some("xcodebuild -version"),
+        "Betas ship SDKs and toolchains with their own quirks, which can explain failures that don't happen with a stable Xcode.",
+        "If you didn't mean to use the beta, run `sudo xcode-select --switch /Applications/Xcode.app`.",
+    ),
+    explain!(
+        "apple.macos-version",
+        "Whether your macOS version can run the installed Xcode.",
+        Some("sw_vers"),
+        "Each Xcode needs a minimum macOS version, and refuses to run on anything older.",
+        "Update macOS, or install an older Xcode.",
+    ),
+    explain!(
+        "apple.ios-deploy",
+        "Which version of ios-deploy is installed.",
+        Some("ios-deploy --version"),
+        "`cargo apple run` uses ios-deploy to install and launch apps on devices.",
+        "Run `brew install ios-deploy`.",
+    ),
+    explain!(
+        "apple.deploy-backend",
+        "Which tool will be used to deploy to devices.",
+        Some("xcrun --find devicectl"),
+        "Newer Xcodes come with `devicectl`, which can check on devices but that we can't deploy with yet.",
+        "Install ios-deploy with `brew install ios-deploy`.",
+    ),
+    explain!(
+        "apple.xcodegen",
+        "Which version of XcodeGen is installed.",
+        Some("xcodegen --version"),
+        "The Xcode project is generated with XcodeGen.",
+        "Run `brew install xcodegen`.",
+    ),
+    explain!(
+        "apple.xcode-plugin",
+        "Whether the Xcode Rust plugin is installed and up-to-date (slow; only with `--include-slow`).",
+        None,
+        "Without the plugin, Xcode doesn't highlight or recognize Rust sources.",
+        "Run `cargo mobile init` to install or update it.",
+    ),
+    explain!(
+        "apple.simulator-runtimes",
+        "Which iOS simulator runtimes are installed.",
+        Some("xcrun simctl list runtimes --json"),
+        "Apps can only be run on a simulator for an iOS version that has a runtime installed.",
+        "Install a runtime from Xcode's Platforms settings.",
+    ),
+    explain!(
+        "apple.simulator",
+        "Whether the simulator named in the project config exists.",
+        Some("xcrun simctl list devices --json"),
+        "Xcode updates tend to prune simulators, which otherwise only comes up when running on the missing one.",
+        "Create the simulator in Xcode's Devices and Simulators window, or update `apple.simulator` in the project config.",
+    ),
+    explain!(
+        "apple.signing-identities",
+        "Which code signing identities are in your keychain.",
+        Some("security find-identity -v -p codesigning"),
+        "Running on a device needs a valid signing identity, and an expired one fails at the very end of a build.",
+        "Sign in to your Apple account in Xcode's Accounts settings, and let Xcode create a certificate.",
+    ),
+    explain!(
+        "apple.teams",
+        "Which development teams your signing identities belong to.",
+        None,
+        "The project config's `apple.development-team` needs to be one of these for signing to work.",
+        "Sign in to your Apple account in Xcode, then set `apple.development-team` to one of the listed IDs.",
+    ),
+    explain!(
+        "apple.python",
+        "Whether `python3` is Python 3 and runs.",
+        Some("python3 --version"),
+        "Some build scripts and CocoaPods plugins run `python3`.",
+        "Install Python 3, i.e. with `brew install python`.",
+    ),
+    explain!(
+        "apple.cocoapods",
+        "Which version of CocoaPods is installed, if any.",
+        Some("pod --version"),
+        "Projects with pods can't build without CocoaPods.",
+        "Run `brew install cocoapods`.",
+    ),
+    explain!(
+        "apple.bundle-id",
+        "Whether the bundle ID is valid.",
+        None,
+        "App Store Connect and code signing reject bundle IDs with invalid characters, but only late in the process.",
+        "Change `app.domain` or `app.name` in the project config so the bundle ID only has letters, numbers, hyphens, and periods.",
+    ),
+    explain!(
+        "apple.ios-targets",
+        "Which Rust target each of the project's iOS archs builds with, and whether it's installed.",
+        Some("rustup target list --installed"),
+        "A project can build for devices while missing the simulator's target, or vice versa.",
+        "Fix `valid-archs` if an arch doesn't correspond to any iOS target; install missing targets with `rustup target add <triple>`.",
+    ),
+    explain!(
+        "apple.ios-sdk",
+        "Whether the selected iOS SDK supports the project's deployment target.",
+        Some("xcodebuild -showsdks"),
+        "A partially-updated Xcode can leave an old SDK selected, which can't build for newer deployment targets.",
+        "Update Xcode, or lower `apple.ios-version` in the project config.",
+    ),
+    explain!(
+        "apple.derived-data",
+        "How big Xcode's DerivedData folder is (slow; only with `--include-slow`).",
+        Some("du -sk ~/Library/Developer/Xcode/DerivedData"),
+        "DerivedData grows forever, and a big one is usually full of stale builds that Xcode may pick up instead of fresh ones.",
+        "Delete `~/Library/Developer/Xcode/DerivedData`, or raise `doctor.derived-data-limit-gb` in the project config.",
+    ),
+    explain!(
+        "apple.build-scripts",
+        "Whether the scripts Xcode runs during the build are executable.",
+        None,
+        "A missing exec bit only shows up as a cryptic \"Permission denied\" in the build log.",
+        "Run `chmod +x` on each listed script.",
+    ),
+    explain!(
+        "android.sdk",
+        "Which Android SDK is used, and where it's installed.",
+        None,
+        "Everything on the Android side is built with the SDK.",
+        "Install the SDK with Android Studio, then set `ANDROID_SDK_ROOT` to its location.",
+    ),
+    explain!(
+        "android.env",
+        "Whether the Android SDK and NDK could be found at all.",
+        None,
+        "None of the other Android checks can run without them.",
+        "Set `ANDROID_SDK_ROOT` and `NDK_HOME` to their locations.",
+    ),
+    explain!(
+        "android.env-paths",
+        "Whether `ANDROID_SDK_ROOT`, `ANDROID_HOME`, and `NDK_HOME` point at existing paths.",
+        None,
+        "Uninstalling an SDK or NDK tends to leave dangling symlinks, which show up as confusing failures later on.",
+        "Point each variable at an existing installation.",
+    ),
+    explain!(
+        "android.sdk-root-vars",
+        "Whether `ANDROID_SDK_ROOT` and `ANDROID_HOME` agree.",
+        None,
+        "Different tools prefer different variables, so if these disagree, it's anybody's guess which SDK a build uses.",
+        "Set both to the same path, or unset `ANDROID_HOME`.",
+    ),
+    explain!(
+        "android.ndk",
+        "Which NDK is used, and where it's installed.",
+        None,
+        "Rust code is compiled and linked with the NDK's toolchain.",
+        "Install the NDK with Android Studio's SDK Manager, then set `NDK_HOME` to its location.",
+    ),
+    explain!(
+        "android.expected-ndk",
+        "Whether the NDK version matches the one the project's doctor config expects.",
+        None,
+        "Teams pin tool versions so everyone's builds behave the same.",
+        "Install the expected version, or update `doctor.expected.ndk` in the project config.",
+    ),
+    explain!(
+        "android.ndk-range",
+        "Whether the NDK is within the range set by `android.ndk.min` and `android.ndk.max` in the project config.",
+        None,
+        "Linking tends to break subtly, rather than outright, on an NDK the project wasn't written for.",
+        "Run `sdkmanager \"ndk;<version>\"` with the violated bound if it's a whole version, or `sdkmanager --list` to find an NDK in the range if it isn't, then point `NDK_HOME` at it.",
+    ),
+    explain!(
+        "android.ndk-host",
+        "Whether the NDK's prebuilt toolchain is for this machine.",
+        None,
+        "An NDK copied from another machine can have the wrong host's toolchain, which only shows up once linking fails.",
+        "Install the NDK for this machine with Android Studio's SDK Manager.",
+    ),
+    explain!(
+        "android.linkers",
+        "Whether the NDK has a working linker for each Android target.",
+        Some("<ndk>/toolchains/llvm/prebuilt/<host>/bin/<triple><min-sdk-version>-clang --version"),
+        "We link with the NDK's `clang` wrappers, which only exist for the API levels that the NDK supports.",
+        "Install an NDK that supports your `android.min-sdk-version`, or change it in the project config.",
+    ),
+    explain!(
+        "android.installed-ndks",
+        "Which NDKs are installed in the SDK.",
+        None,
+        "It's easy to end up building with a different NDK than the one you installed.",
+        "Set `NDK_HOME` to the NDK you want to use.",
+    ),
+    explain!(
+        "android.build-tools",
+        "Which versions of the SDK build tools are installed.",
+        None,
+        "Gradle needs the build tools for the project's compile SDK version.",
+        "Install the build tools with Android Studio's SDK Manager.",
+    ),
+    explain!(
+        "android.compile-sdk",
+        "Whether the SDK platform the project compiles against is installed.",
+        None,
+        "Gradle needs `platforms/android-<N>` for the compile SDK, and fails with a confusing error when it's missing.",
+        "Run `sdkmanager \"platforms;android-<N>\"`.",
+    ),
+    explain!(
+        "android.sdk-licenses",
+        "Whether the SDK licenses have been accepted.",
+        None,
+        "Gradle refuses to download missing SDK components until the licenses are accepted.",
+        "Run `sdkmanager --licenses` and accept them.",
+    ),
+    explain!(
+        "android.free-space",
+        "How much disk space is left where the SDK is installed.",
+        None,
+        "SDK components, emulator images, and Gradle caches are large, and running out fails builds in odd ways.",
+        "Free up some disk space.",
+    ),
+    explain!(
+        "android.emulator-accel",
+        "Whether the emulator can use hardware acceleration.",
+        Some("emulator -accel-check"),
+        "Without acceleration, the emulator is too slow to be usable, if it even runs.",
+        "Enable virtualization in your BIOS, and on Linux, add yourself to the `kvm` group.",
+    ),
+    explain!(
+        "android.rosetta",
+        "Whether Rosetta is installed on Apple Silicon.",
+        None,
+        "x86 emulator images can only run through Rosetta, and without it the emulator crashes without saying why.",
+        "Run `softwareupdate --install-rosetta`.",
+    ),
+    explain!(
+        "android.avds",
+        "Which emulators (AVDs) exist.",
+        Some("emulator -list-avds"),
+        "`cargo android run` can only start an emulator if there's an AVD for it to run.",
+        "Create an AVD with Android Studio's Device Manager.",
+    ),
+    explain!(
+        "android.java",
+        "Which version of Java is installed.",
+        Some("java -version"),
+        "Gradle runs on Java, and the Android Gradle Plugin our templates use needs Java 11 or newer.",
+        "Install Java 11 or newer, and set `JAVA_HOME` to it.",
+    ),
+    explain!(
+        "android.application-id",
+        "Whether the application ID is valid.",
+        None,
+        "Gradle and the Play Store reject invalid application IDs, but only late in the process.",
+        "Change `app.domain` or `app.name` in the project config so each part of the ID starts with a letter.",
+    ),
+    explain!(
+        "android.release-keystore",
+        "Whether the release keystore exists and opens with the configured password.",
+        Some("keytool -list -keystore <keystore> -alias <alias> -storepass:env ANDROID_KEYSTORE_PASSWORD"),
+        "A missing keystore or wrong password otherwise only shows up at the very end of a release build.",
+        "Fix `android.release-signing` in the project config, or set `ANDROID_KEYSTORE_PASSWORD` to the right password.",
+    ),
+    explain!(
+        "android.gradlew",
+        "Whether the project's `gradlew` is executable.",
+        None,
+        "Builds run `gradlew` directly, which fails with \"Permission denied\" without the exec bit.",
+        "Run `chmod +x gen/android/<app>/gradlew`, or run `cargo mobile init` to regenerate it.",
+    ),
+    explain!(
+        "android.build-scripts",
+        "Whether the shell scripts in the generated Android project are executable.",
+        None,
+        "A missing exec bit only shows up as a cryptic \"Permission denied\" in the build log.",
+        "Run `chmod +x` on each listed script.",
+    ),
+    explain!(
+        "android.gradle-agp",
+        "Whether the project's Gradle wrapper is new enough for its Android Gradle Plugin.",
+        None,
+        "Each Android Gradle Plugin release needs a minimum Gradle version, and the build fails before it gets anywhere otherwise.",
+        "Run `./gradlew wrapper --gradle-version <version>` in the Android project, or use an older Android Gradle Plugin.",
+    ),
+    explain!(
+        "device-list.android",
+        "Which Android devices and emulators are connected.",
+        Some("adb devices"),
+        "`cargo android run` needs a usable device, and unauthorized or offline ones can't be deployed to.",
+        "Connect a device with USB debugging enabled and accept the prompt on it, or start an emulator.",
+    ),
+    explain!(
+        "device-list.ios",
+        "Which iOS devices are connected.",
+        Some("ios-deploy --detect"),
+        "`cargo apple run` needs a trusted device with a supported iOS version.",
+        "Connect a device, unlock it, and tap \"Trust\" when asked.",
+    ),
+];
+
+/// Looks up the explanation for the check with this ID.
+pub fn lookup(id: &str) -> Result<&'static Explanation, UnknownCheck> {
+    EXPLANATIONS
+        .iter()
+        .find(|explanation| explanation.id == id)
+        .ok_or_else(|| UnknownCheck {
+            id: id.to_owned(),
+            possible: EXPLANATIONS
+                .iter()
+                .map(|explanation| explanation.id)
+                .collect(),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn ids_are_unique() {
+        let mut seen = HashSet::new();
+        for explanation in EXPLANATIONS {
+            assert!(
+                seen.insert(explanation.id),
+                "{:?} is explained twice",
+                explanation.id
+            );
+        }
+    }
+}
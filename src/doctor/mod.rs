@@ -1,9 +1,39 @@
+pub mod config;
+mod explain;
+mod plugin;
 mod section;
 
+pub use self::explain::{Explanation, UnknownCheck};
+pub use self::plugin::{Ctx, DoctorCheck};
+pub use self::section::{
+    device_list::{Connection, DeviceSummary, Platform},
+    since::{LoadError as SinceLoadError, Snapshot},
+    FixAction, Item, Label, PrintOpts, Section, Symbols,
+};
+
 use crate::{
+    config::{Config, LoadExistingError},
     env::{self, Env},
-    util::{self, cli::TextWrapper},
+    opts::{
+        FirstError, Fix, ForceColor, IncludeSlow, NoColor, NoDeviceChecks, NoiseLevel,
+        NonInteractive, OmitSkipped,
+    },
+    util::{
+        self,
+        cli::{self, TextWrapper},
+        prompt,
+    },
+};
+use serde::Serialize;
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
 };
+use structopt::clap::arg_enum;
 use thiserror::Error;
 
 // This should only be used for errors that we *really* don't expect and/or
@@ -18,14 +48,723 @@ pub enum Unrecoverable {
     // home or some other path isn't valid UTF-8
     #[error("Failed to prettify path: {0}")]
     ContractHomeFailed(#[from] util::ContractHomeError),
+    // Only encountered if `serde_json` chokes on our own plain data types
+    #[error("Failed to serialize report: {0}")]
+    SerializeFailed(#[from] serde_json::Error),
+    // Only encountered if stdin/stdout are unusable
+    #[error("Failed to prompt for fix: {0}")]
+    PromptFailed(#[from] std::io::Error),
+    // Only encountered if the `--output` path is somewhere we can't write to
+    #[error("Failed to create report file {path:?}: {source}")]
+    OutputCreationFailed { path: PathBuf, source: io::Error },
+    // Only encountered if the disk fills up or stdout is closed on us
+    #[error("Failed to write report: {0}")]
+    WriteFailed(#[source] io::Error),
+    // Only encountered if the `--since` report is missing or was hand-edited
+    #[error(transparent)]
+    SinceLoadFailed(#[from] SinceLoadError),
+    // Only encountered if the `--save` path is somewhere we can't write to
+    #[error("Failed to save report to {path:?}: {source}")]
+    SaveFailed { path: PathBuf, source: io::Error },
 }
 
-pub fn exec(wrapper: &TextWrapper) -> Result<(), Unrecoverable> {
-    let env = Env::new()?;
-    section::cargo_mobile::check()?.print(wrapper);
+arg_enum! {
+    /// Output format for the doctor report
+    #[derive(Clone, Copy, Debug)]
+    pub enum Format {
+        Text,
+        Json,
+        Github,
+    }
+}
+
+arg_enum! {
+    /// Lowest severity that makes the doctor exit with a non-zero status
+    #[derive(Clone, Copy, Debug)]
+    pub enum FailOn {
+        Warning,
+        Error,
+    }
+}
+
+impl FailOn {
+    pub fn exit_code(self, worst: Label) -> i32 {
+        match (self, worst) {
+            (_, Label::Error) => 1,
+            (Self::Warning, Label::Warning) => 2,
+            _ => 0,
+        }
+    }
+}
+
+/// Options controlling which checks run and how.
+#[derive(Clone, Debug)]
+pub struct CheckOpts {
+    /// When not polite, sections note the commands they run.
+    pub noise_level: NoiseLevel,
+    /// Run checks that are too slow to run by default, i.e. ones that hit the
+    /// network.
+    pub include_slow: IncludeSlow,
+    /// IDs of checks to leave out, on top of the ones in the project config's
+    /// `doctor.skip`.
+    pub skip: Vec<String>,
+    /// Leave skipped checks out entirely, rather than noting that they were
+    /// skipped.
+    pub omit_skipped: OmitSkipped,
+    /// How long to wait on commands that are prone to hanging (i.e. device
+    /// enumeration) before giving up on them.
+    pub timeout: Duration,
+    /// Check one section at a time, stopping at the first error, and keep
+    /// only that error.
+    pub first_error: FirstError,
+    /// Only check the Rust target and linker for this triple, rather than
+    /// for every target cargo-mobile supports.
+    pub target: Option<String>,
+    /// Leave out the connected devices, which is the default on CI (see
+    /// [`CheckOpts::device_checks_skipped_by_default`]).
+    pub no_device_checks: NoDeviceChecks,
+}
+
+impl Default for CheckOpts {
+    fn default() -> Self {
+        Self {
+            noise_level: Default::default(),
+            include_slow: IncludeSlow::No,
+            skip: Default::default(),
+            omit_skipped: OmitSkipped::No,
+            timeout: Self::DEFAULT_TIMEOUT,
+            first_error: FirstError::No,
+            target: None,
+            no_device_checks: NoDeviceChecks::No,
+        }
+    }
+}
+
+impl CheckOpts {
+    // Device enumeration is the most likely thing to hang (i.e. on a wedged
+    // `adb` server), so we give up on it after this long rather than freezing
+    // the whole report.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// CI machines never have any devices attached, so enumerating them just
+    /// costs time. Most CI services set `CI`, usually to `true`.
+    pub fn device_checks_skipped_by_default() -> bool {
+        std::env::var_os("CI").map_or(false, |ci| !ci.is_empty() && ci != "false" && ci != "0")
+    }
+
+    /// Whether checks for `triple` should run, given `target`.
+    pub fn includes_target(&self, triple: &str) -> bool {
+        self.target
+            .as_deref()
+            .map_or(true, |target| target == triple)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Failed to list installed Rust targets: {0}")]
+pub struct ListTargetsFailed(section::Error);
+
+/// The Rust targets that the project in the current directory builds for, but
+/// that aren't installed, for provisioning scripts and the like. Outside of a
+/// project, that's everything that `cargo mobile init` installs.
+pub fn missing_rust_targets() -> Result<Vec<String>, ListTargetsFailed> {
+    use section::cargo_mobile::{all_targets, load_metadata, missing_targets, required_targets};
+    let required = match Config::load(".") {
+        Ok(Some(config)) => required_targets(&load_metadata(&config)),
+        _ => all_targets(),
+    };
+    missing_targets(required)
+        .map(|missing| missing.into_iter().map(ToOwned::to_owned).collect())
+        .map_err(ListTargetsFailed)
+}
+
+#[derive(Debug, Error)]
+#[error("Section {name:?} is invalid; the possible sections are {possible:?}")]
+pub struct SectionIdInvalid {
+    name: String,
+    possible: Vec<&'static str>,
+}
+
+#[derive(Debug, Error)]
+#[error("Target {triple:?} isn't supported; the supported targets are {possible:?}")]
+pub struct TargetInvalid {
+    triple: String,
+    possible: Vec<&'static str>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SectionId {
+    CargoMobile,
     #[cfg(target_os = "macos")]
-    section::apple::check().print(wrapper);
-    section::android::check(&env)?.print(wrapper);
-    section::device_list::check(&env).print(wrapper);
+    Apple,
+    Android,
+    DeviceList,
+}
+
+impl FromStr for SectionId {
+    type Err = SectionIdInvalid;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|id| id.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| SectionIdInvalid {
+                name: s.to_owned(),
+                possible: Self::ALL.iter().map(|id| id.as_str()).collect(),
+            })
+    }
+}
+
+impl SectionId {
+    /// All sections available on this platform, in the order they're printed.
+    pub const ALL: &'static [Self] = &[
+        Self::CargoMobile,
+        #[cfg(target_os = "macos")]
+        Self::Apple,
+        Self::Android,
+        Self::DeviceList,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::CargoMobile => "cargo-mobile",
+            #[cfg(target_os = "macos")]
+            Self::Apple => "apple",
+            Self::Android => "android",
+            Self::DeviceList => "device-list",
+        }
+    }
+
+    /// The section for the platform that `triple` builds for.
+    pub fn for_target(triple: &str) -> Result<Self, TargetInvalid> {
+        let android = crate::android::target::Target::all()
+            .values()
+            .any(|target| target.triple == triple);
+        #[cfg(target_os = "macos")]
+        let apple = crate::apple::target::Target::all()
+            .values()
+            .any(|target| target.triple == triple);
+        #[cfg(not(target_os = "macos"))]
+        let apple = false;
+        match (android, apple) {
+            (true, _) => Ok(Self::Android),
+            #[cfg(target_os = "macos")]
+            (_, true) => Ok(Self::Apple),
+            _ => Err(TargetInvalid {
+                triple: triple.to_owned(),
+                possible: section::cargo_mobile::all_targets(),
+            }),
+        }
+    }
+
+    fn check(
+        self,
+        env: &Env,
+        config: &Result<Option<Config>, LoadExistingError>,
+        opts: &CheckOpts,
+    ) -> Result<Section, Unrecoverable> {
+        // Project checks just get skipped if the config is absent or broken;
+        // the cargo-mobile section is the one that reports on the config itself.
+        let project = config.as_ref().ok().and_then(Option::as_ref);
+        match self {
+            Self::CargoMobile => section::cargo_mobile::check(config, opts),
+            #[cfg(target_os = "macos")]
+            Self::Apple => Ok(section::apple::check(project, opts)),
+            Self::Android => section::android::check(env, project, opts),
+            Self::DeviceList => Ok(section::device_list::check(env, project, opts)),
+        }
+    }
+}
+
+/// Resolves section names into the order to run and print sections in. Unknown
+/// names are warned about and skipped, and sections that weren't named follow
+/// in their default order.
+pub fn section_order(names: &[String]) -> Vec<SectionId> {
+    let mut order = Vec::with_capacity(SectionId::ALL.len());
+    for name in names {
+        match name.parse::<SectionId>() {
+            Ok(id) if !order.contains(&id) => order.push(id),
+            Ok(_) => (),
+            Err(err) => eprintln!("Warning: {}", err),
+        }
+    }
+    for id in SectionId::ALL {
+        if !order.contains(id) {
+            order.push(*id);
+        }
+    }
+    order
+}
+
+// Commands that a section ran outside of any check are echoed at the end.
+fn echoed(
+    opts: &CheckOpts,
+    check: impl FnOnce() -> Result<Section, Unrecoverable>,
+) -> Result<Section, Unrecoverable> {
+    let (section, leftover) = section::echoing(opts.noise_level, check);
+    section.map(|section| section.with_items(leftover))
+}
+
+fn echoed_extra(opts: &CheckOpts, check: &dyn DoctorCheck, ctx: &Ctx<'_>) -> Section {
+    let (section, leftover) = section::echoing(opts.noise_level, || check.section(ctx));
+    section.with_items(leftover)
+}
+
+fn join<T>(handle: thread::ScopedJoinHandle<'_, T>) -> T {
+    handle
+        .join()
+        .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+}
+
+/// A compact summary of a [`Report`], for status endpoints and the like that
+/// don't want the full list of items.
+#[derive(Clone, Debug, Serialize)]
+pub struct Health {
+    pub worst: Label,
+    pub passed: usize,
+    pub warnings: usize,
+    pub errors: usize,
+    pub sections: Vec<(String, Label)>,
+}
+
+/// The results of every check, kept separate from printing so that they can be
+/// inspected or embedded elsewhere.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Report {
+    sections: Vec<Section>,
+    // Wall time for the whole run, which is less than the sum of the
+    // sections' since they run in parallel.
+    #[serde(skip)]
+    elapsed: Duration,
+}
+
+impl Report {
+    /// Runs the checks for the given sections, which are kept in the given
+    /// order.
+    pub fn run(opts: &CheckOpts, ids: &[SectionId]) -> Result<Self, Unrecoverable> {
+        Self::run_with(opts, ids, &[])
+    }
+
+    /// Like [`Report::run`], but with `extra` sections following the built-in
+    /// ones.
+    pub fn run_with(
+        opts: &CheckOpts,
+        ids: &[SectionId],
+        extra: &[Box<dyn DoctorCheck>],
+    ) -> Result<Self, Unrecoverable> {
+        let began = Instant::now();
+        let env = &Env::new()?;
+        let config = &Config::load(".");
+        let mut skip = opts.skip.clone();
+        let mut messages = Default::default();
+        if let Ok(Some(config)) = config {
+            skip.extend_from_slice(config.doctor().skip());
+            messages = config.doctor().messages().clone();
+        }
+        let shape = |section: Section| {
+            // Plugin sections and merged checks can easily repeat each other.
+            let mut section = section.skip(&skip, opts.omit_skipped).localize(&messages);
+            section.dedup();
+            section
+        };
+        #[cfg(target_os = "macos")]
+        if ids.contains(&SectionId::Apple) {
+            if let Err(err) = section::apple::offer_xcode_developer_dir() {
+                eprintln!("Warning: {}", err);
+            }
+        }
+        if opts.first_error.yes() {
+            let ctx = Ctx {
+                env,
+                config: config.as_ref().ok().and_then(Option::as_ref),
+                opts,
+            };
+            // Going one section at a time is slower overall, but means we can
+            // stop as soon as something fails.
+            let mut sections = Vec::new();
+            for id in ids {
+                let section = echoed(opts, || id.check(env, config, opts))?;
+                if let Some(section) = shape(section).into_first_error() {
+                    sections.push(section);
+                    break;
+                }
+            }
+            if sections.is_empty() {
+                sections.extend(extra.iter().find_map(|check| {
+                    shape(echoed_extra(opts, &**check, &ctx)).into_first_error()
+                }));
+            }
+            return Ok(Self {
+                sections,
+                elapsed: began.elapsed(),
+            });
+        }
+        // The sections don't depend on each other, so we run them all at once
+        // and then collect them in a fixed order to keep the output stable.
+        let sections = thread::scope(|scope| {
+            ids.iter()
+                .copied()
+                .map(|id| {
+                    scope.spawn(move || {
+                        let began = Instant::now();
+                        echoed(opts, || id.check(env, config, opts))
+                            .map(|section| section.with_duration(began.elapsed()))
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(join)
+                .collect::<Result<Vec<_>, _>>()
+                .map(|mut sections| {
+                    let ctx = Ctx {
+                        env,
+                        config: config.as_ref().ok().and_then(Option::as_ref),
+                        opts,
+                    };
+                    let extra = extra
+                        .iter()
+                        .map(|check| {
+                            scope.spawn(move || {
+                                let began = Instant::now();
+                                echoed_extra(opts, &**check, &ctx).with_duration(began.elapsed())
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    sections.extend(extra.into_iter().map(join));
+                    sections
+                })
+        })?;
+        let sections = sections.into_iter().map(shape).collect();
+        Ok(Self {
+            sections,
+            elapsed: began.elapsed(),
+        })
+    }
+
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    pub fn into_sections(self) -> Vec<Section> {
+        self.sections
+    }
+
+    pub fn worst_label(&self) -> Label {
+        Label::worst(self.sections.iter().map(Section::worst_label))
+    }
+
+    pub fn health(&self) -> Health {
+        let (passed, warnings, errors) = self
+            .sections
+            .iter()
+            .map(Section::counts)
+            .fold((0, 0, 0), |(passed, warnings, errors), (v, w, e)| {
+                (passed + v, warnings + w, errors + e)
+            });
+        Health {
+            worst: self.worst_label(),
+            passed,
+            warnings,
+            errors,
+            sections: self
+                .sections
+                .iter()
+                .map(|section| (section.title().to_owned(), section.label()))
+                .collect(),
+        }
+    }
+
+    /// Prints every warning and error as a GitHub Actions workflow command, so
+    /// that they show up as annotations on the run.
+    pub fn print_github(&self, out: &mut impl Write) -> io::Result<()> {
+        for line in self.sections.iter().flat_map(Section::github_annotations) {
+            writeln!(out, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// A colorless summary of the environment and every problem, fenced for
+    /// pasting into a GitHub issue. It's never wrapped, since GitHub does that
+    /// on its own.
+    pub fn issue_block(&self) -> String {
+        static FACTS: &[(&str, &str)] = &[
+            ("OS", "cargo-mobile.os"),
+            ("cargo-mobile", "cargo-mobile.version"),
+            ("Rust", "cargo-mobile.rust"),
+            ("Xcode", "apple.xcode"),
+            ("NDK", "android.ndk"),
+        ];
+        let mut lines = vec!["```text".to_owned()];
+        // Checks that didn't run (i.e. Xcode on Linux) are left out rather than
+        // reported as unknown.
+        lines.extend(FACTS.iter().filter_map(|(name, id)| {
+            self.sections
+                .iter()
+                .find_map(|section| section.message_of(id))
+                .map(|msg| format!("{}: {}", name, msg))
+        }));
+        let problems = self
+            .sections
+            .iter()
+            .flat_map(Section::problem_lines)
+            .collect::<Vec<_>>();
+        if problems.is_empty() {
+            lines.push("Problems: none".to_owned());
+        } else {
+            lines.push("Problems:".to_owned());
+            lines.extend(problems);
+        }
+        lines.push("```".to_owned());
+        lines.join("\n")
+    }
+
+    /// The fixes for every item that has one, without duplicates.
+    pub fn fix_actions(&self) -> Vec<FixAction> {
+        let mut actions = Vec::<FixAction>::new();
+        for action in self.sections.iter().flat_map(Section::fix_actions) {
+            if !actions.contains(action) {
+                actions.push(action.clone());
+            }
+        }
+        actions
+    }
+
+    pub fn print(
+        &self,
+        out: &mut impl Write,
+        wrapper: &TextWrapper,
+        opts: PrintOpts,
+    ) -> io::Result<()> {
+        // Only `--first-error` leaves the report empty.
+        if self.sections.is_empty() {
+            return writeln!(out, "No errors found");
+        }
+        if opts.problems_only.yes() && matches!(self.worst_label(), Label::Victory) {
+            return writeln!(out, "All checks passed");
+        }
+        for section in &self.sections {
+            section.print(out, wrapper, opts)?;
+        }
+        section::print_summary(
+            out,
+            &self.sections,
+            self.worst_label(),
+            Some(self.elapsed),
+            wrapper,
+            opts,
+        )
+    }
+}
+
+/// Runs every section available on this platform, followed by the sections
+/// from `extra`, for embedding the doctor in other tools.
+pub fn check_all(
+    opts: &CheckOpts,
+    extra: Vec<Box<dyn DoctorCheck>>,
+) -> Result<Vec<Section>, Unrecoverable> {
+    Report::run_with(opts, SectionId::ALL, &extra).map(Report::into_sections)
+}
+
+// Returns whether anything was actually fixed.
+fn apply_fixes(
+    wrapper: &TextWrapper,
+    report: &Report,
+    non_interactive: NonInteractive,
+) -> Result<bool, Unrecoverable> {
+    let mut fixed = false;
+    for action in report.fix_actions() {
+        let confirmed = non_interactive.yes() || {
+            let answer = loop {
+                if let Some(answer) =
+                    prompt::yes_no(format!("\n{}?", action), Some(prompt::YesOrNo::Yes))?
+                {
+                    break answer;
+                }
+            };
+            answer.yes()
+        };
+        if confirmed {
+            match action.run() {
+                Ok(()) => fixed = true,
+                Err(err) => cli::Report::error(format!("Failed to {}", action), err).print(wrapper),
+            }
+        }
+    }
+    Ok(fixed)
+}
+
+// https://no-color.org
+fn no_color_if(print_opts: PrintOpts, no_color: bool) -> PrintOpts {
+    if no_color || std::env::var_os("NO_COLOR").is_some() {
+        PrintOpts {
+            no_color: NoColor::Yes,
+            ..print_opts
+        }
+    } else {
+        print_opts
+    }
+}
+
+/// Writes the report to `output` if given, and to stdout otherwise. Reports
+/// written to a file don't get colored unless `force_color` is set, since the
+/// escape codes just get in the way of reading them elsewhere.
+#[allow(clippy::too_many_arguments)]
+pub fn exec(
+    wrapper: &TextWrapper,
+    check_opts: &CheckOpts,
+    non_interactive: NonInteractive,
+    format: Format,
+    ids: &[SectionId],
+    print_opts: PrintOpts,
+    fix: Fix,
+    output: Option<&Path>,
+    force_color: ForceColor,
+    since: Option<&Path>,
+    save: Option<&Path>,
+) -> Result<Label, Unrecoverable> {
+    // Loading this first means a bad path fails fast, rather than after all
+    // the checks have run.
+    let before = since
+        .map(|path| Snapshot::load(path).map(|snapshot| (path, snapshot)))
+        .transpose()?;
+    let print_opts = no_color_if(print_opts, output.is_some() && force_color.no());
+    let mut file;
+    let mut stdout;
+    let mut out: &mut dyn Write = match output {
+        Some(path) => {
+            file = File::create(path).map_err(|source| Unrecoverable::OutputCreationFailed {
+                path: path.to_owned(),
+                source,
+            })?;
+            &mut file
+        }
+        None => {
+            stdout = io::stdout();
+            &mut stdout
+        }
+    };
+    let mut report = Report::run(check_opts, ids)?;
+    match format {
+        Format::Text => {
+            report
+                .print(&mut out, wrapper, print_opts)
+                .map_err(Unrecoverable::WriteFailed)?;
+            // Prompting in the middle of JSON output would just make a mess.
+            if fix.yes() && apply_fixes(wrapper, &report, non_interactive)? {
+                writeln!(out, "\nChecking again...").map_err(Unrecoverable::WriteFailed)?;
+                report = Report::run(check_opts, ids)?;
+                report
+                    .print(&mut out, wrapper, print_opts)
+                    .map_err(Unrecoverable::WriteFailed)?;
+            }
+            if let Some((path, before)) = &before {
+                Snapshot::of(&report)?
+                    .print_changes(before, path, &mut out, wrapper, print_opts)
+                    .map_err(Unrecoverable::WriteFailed)?;
+            }
+        }
+        Format::Json => writeln!(out, "{}", serde_json::to_string_pretty(&report)?)
+            .map_err(Unrecoverable::WriteFailed)?,
+        Format::Github => report
+            .print_github(&mut out)
+            .map_err(Unrecoverable::WriteFailed)?,
+    }
+    if let Some(path) = save {
+        fs::write(path, serde_json::to_string_pretty(&report)?).map_err(|source| {
+            Unrecoverable::SaveFailed {
+                path: path.to_owned(),
+                source,
+            }
+        })?;
+    }
+    Ok(report.worst_label())
+}
+
+/// Re-runs the checks every `interval` until interrupted, redrawing the report
+/// whenever it changes.
+pub fn watch(
+    wrapper: &TextWrapper,
+    check_opts: &CheckOpts,
+    format: Format,
+    ids: &[SectionId],
+    print_opts: PrintOpts,
+    interval: Duration,
+) -> Result<(), Unrecoverable> {
+    // Clears the screen and moves the cursor to the top-left.
+    static CLEAR: &str = "\x1b[2J\x1b[H";
+    let print_opts = no_color_if(print_opts, false);
+    let mut last = String::new();
+    loop {
+        let report = Report::run(check_opts, ids)?;
+        // Redrawing an unchanged report just makes the terminal flicker. The
+        // timings differ every run, so we compare the JSON, which leaves them
+        // out.
+        let snapshot = serde_json::to_string(&report)?;
+        if snapshot != last {
+            let mut rendered = Vec::new();
+            match format {
+                Format::Text => report.print(&mut rendered, wrapper, print_opts),
+                Format::Json => writeln!(rendered, "{}", serde_json::to_string_pretty(&report)?),
+                Format::Github => report.print_github(&mut rendered),
+            }
+            .map_err(Unrecoverable::WriteFailed)?;
+            let mut stdout = io::stdout();
+            stdout
+                .write_all(CLEAR.as_bytes())
+                .and_then(|()| stdout.write_all(&rendered))
+                .and_then(|()| stdout.flush())
+                .map_err(Unrecoverable::WriteFailed)?;
+            last = snapshot;
+        }
+        thread::sleep(interval);
+    }
+}
+
+/// Prints a single token summarizing the report, for shell prompts and status
+/// bars, and returns the worst label. Slow checks never run, since this is
+/// meant to be run constantly.
+pub fn exec_status(check_opts: &CheckOpts, ids: &[SectionId]) -> Result<Label, Unrecoverable> {
+    let check_opts = CheckOpts {
+        include_slow: IncludeSlow::No,
+        ..check_opts.clone()
+    };
+    let worst = Report::run(&check_opts, ids)?.worst_label();
+    println!(
+        "mobile:{}",
+        match worst {
+            Label::Victory | Label::Info => "ok",
+            Label::Warning => "warn",
+            Label::Error => "err",
+        }
+    );
+    Ok(worst)
+}
+
+/// Prints just the block from [`Report::issue_block`].
+pub fn exec_issue(check_opts: &CheckOpts, ids: &[SectionId]) -> Result<(), Unrecoverable> {
+    println!("{}", Report::run(check_opts, ids)?.issue_block());
+    Ok(())
+}
+
+/// Prints the long-form help for a single check, without running anything.
+pub fn exec_explain(wrapper: &TextWrapper, id: &str) -> Result<(), UnknownCheck> {
+    explain::lookup(id).map(|explanation| explanation.print(wrapper))
+}
+
+/// Prints just the [`Health`] as JSON.
+pub fn exec_health(check_opts: &CheckOpts, ids: &[SectionId]) -> Result<(), Unrecoverable> {
+    let health = Report::run(check_opts, ids)?.health();
+    println!("{}", serde_json::to_string_pretty(&health)?);
+    Ok(())
+}
+
+/// Prints just the connected devices as JSON, for editor integrations and the
+/// like.
+pub fn exec_devices() -> Result<(), Unrecoverable> {
+    let env = Env::new()?;
+    let summaries = section::device_list::summarize(&env);
+    println!("{}", serde_json::to_string_pretty(&summaries)?);
     Ok(())
 }
@@ -0,0 +1,35 @@
+use super::{CheckOpts, Section};
+use crate::{config::Config, env::Env};
+
+/// What a [`DoctorCheck`] gets to look at.
+#[derive(Clone, Copy, Debug)]
+pub struct Ctx<'a> {
+    pub(super) env: &'a Env,
+    pub(super) config: Option<&'a Config>,
+    pub(super) opts: &'a CheckOpts,
+}
+
+impl<'a> Ctx<'a> {
+    pub fn env(&self) -> &'a Env {
+        self.env
+    }
+
+    /// The project config, if we're in a project and it loaded successfully.
+    pub fn config(&self) -> Option<&'a Config> {
+        self.config
+    }
+
+    pub fn opts(&self) -> &'a CheckOpts {
+        self.opts
+    }
+}
+
+/// A section of project-specific checks (i.e. for an internal signing server)
+/// to add to the report. These run alongside the built-in sections, so they
+/// need to be shareable between threads.
+///
+/// Item IDs work the same as they do for the built-in checks, so it's worth
+/// giving items IDs like `acme.signing-server` to make them skippable.
+pub trait DoctorCheck: Send + Sync {
+    fn section(&self, ctx: &Ctx<'_>) -> Section;
+}
@@ -1,28 +1,1219 @@
-use super::Section;
-use crate::{android, doctor::Unrecoverable, env::Env, util};
-
-pub fn check(env: &Env) -> Result<Section, Unrecoverable> {
-    let section = Section::new("Android developer tools");
-    Ok(match android::env::Env::from_env(env.clone()) {
-        Ok(android_env) => section
-            // It'd be a bit too inconvenient to use `map` here, since we need
-            // to use `?` within the closures...
-            .with_item(match android_env.sdk_version() {
-                Ok(sdk_version) => Ok(format!(
-                    "SDK v{} installed at {:?}",
-                    sdk_version,
-                    util::contract_home(android_env.sdk_root())?,
-                )),
-                Err(err) => Err(format!("Failed to get SDK version: {}", err)),
+use super::{
+    check_expected, check_script, command, is_executable, message::Message, output_of, FixAction,
+    Item, Label, Section,
+};
+use crate::{
+    android,
+    config::{app::domain, Config},
+    doctor::{CheckOpts, Unrecoverable},
+    env::Env,
+    target::TargetTrait as _,
+    util::{self, VersionTriple},
+};
+use once_cell_regex::regex;
+use std::{
+    collections::BTreeSet,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+// Android Gradle Plugin 7, which our templates use, won't run on anything older.
+const MIN_JAVA_VERSION: u32 = 11;
+
+// Uninstalling an SDK or NDK tends to leave symlinks to it dangling, which
+// otherwise only shows up as confusing "exists but empty" failures later on.
+fn check_env_path(name: &str) -> Result<Item, Unrecoverable> {
+    let path = match std::env::var_os(name) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            return Ok(Item::localized(
+                Label::Info,
+                message!("android.env-path.unset", name = name),
+            ))
+        }
+    };
+    let display = format!("{:?}", util::contract_home(&path)?);
+    Ok(match std::fs::symlink_metadata(&path) {
+        Err(_) => Item::localized(
+            Label::Error,
+            message!("android.env-path.missing", name = name, path = display),
+        )
+        .with_fix(format!("Point `{}` at an existing installation", name)),
+        Ok(metadata) => match path.canonicalize() {
+            Ok(real) if real == path => Item::localized(
+                Label::Info,
+                message!("android.env-path", name = name, path = display),
+            ),
+            Ok(real) => Item::localized(
+                Label::Info,
+                message!(
+                    "android.env-path.resolved",
+                    name = name,
+                    path = display,
+                    real = format!("{:?}", util::contract_home(&real)?),
+                ),
+            ),
+            Err(_) if metadata.file_type().is_symlink() => Item::localized(
+                Label::Error,
+                message!(
+                    "android.env-path.dangling",
+                    name = name,
+                    path = display,
+                    target = format!("{:?}", std::fs::read_link(&path).unwrap_or_default()),
+                ),
+            )
+            .with_fix(format!(
+                "Reinstall what it pointed to, or point `{}` somewhere else",
+                name
+            )),
+            // Something further up the path is a dangling symlink.
+            Err(err) => Item::localized(
+                Label::Error,
+                message!(
+                    "android.env-path.unresolved",
+                    name = name,
+                    path = display,
+                    error = err,
+                ),
+            ),
+        },
+    })
+}
+
+// Different tools prefer different variables, so if these disagree, it's
+// anybody's guess which SDK a given build step ends up using.
+fn check_sdk_root_vars(android_env: &android::env::Env) -> Result<Item, Unrecoverable> {
+    let var = |name: &str| std::env::var_os(name).map(PathBuf::from);
+    let same = |a: &Path, b: &Path| match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    };
+    Ok(match (var("ANDROID_SDK_ROOT"), var("ANDROID_HOME")) {
+        (Some(sdk_root), Some(android_home)) if !same(&sdk_root, &android_home) => Item::localized(
+            Label::Warning,
+            message!(
+                "android.sdk-root-vars.mismatch",
+                sdk_root = format!("{:?}", util::contract_home(&sdk_root)?),
+                android_home = format!("{:?}", util::contract_home(&android_home)?),
+            ),
+        ),
+        _ => Item::localized(
+            Label::Info,
+            message!(
+                "android.sdk-root-vars",
+                path = format!("{:?}", util::contract_home(android_env.sdk_root())?),
+            ),
+        ),
+    })
+}
+
+fn check_installed_ndks(
+    android_env: &android::env::Env,
+    section: Section,
+) -> Result<Section, Unrecoverable> {
+    let ndks = match android::ndk::find_installed(Path::new(android_env.sdk_root())) {
+        Ok(ndks) => ndks,
+        Err(err) => {
+            return Ok(section.with_item(Item::localized(
+                Label::Warning,
+                message!("android.installed-ndks.failed", error = err),
+            )))
+        }
+    };
+    let ndk_home = android_env.ndk.home().canonicalize().ok();
+    let is_selected = |path: &Path| ndk_home.is_some() && path.canonicalize().ok() == ndk_home;
+    let mut section = section;
+    for ndk in &ndks {
+        let path = format!("{:?}", util::contract_home(&ndk.path)?);
+        section = section.with_item(match &ndk.version {
+            Ok(version) if is_selected(&ndk.path) => Item::localized(
+                Label::Info,
+                message!(
+                    "android.installed-ndks.selected",
+                    version = version,
+                    path = path,
+                ),
+            ),
+            Ok(version) => Item::localized(
+                Label::Info,
+                message!("android.installed-ndks", version = version, path = path),
+            ),
+            Err(err) => Item::localized(
+                Label::Warning,
+                message!("android.installed-ndks.invalid", path = path, error = err),
+            ),
+        });
+    }
+    // Gradle picks its own NDK from the side-by-side installs, so if
+    // `NDK_HOME` is pointing elsewhere, the two can quietly drift apart.
+    Ok(
+        if ndks.len() > 1 && !ndks.iter().any(|ndk| is_selected(&ndk.path)) {
+            section.with_item(Item::localized(
+                Label::Warning,
+                message!("android.installed-ndks.unselected", count = ndks.len()),
+            ))
+        } else {
+            section
+        },
+    )
+}
+
+// Linking tends to break subtly rather than outright on an NDK the project
+// wasn't written for, i.e. with symbols that moved between releases.
+fn check_ndk_range(android_env: &android::env::Env, config: &Config) -> Option<Item> {
+    let range = config.android().ndk();
+    if range.is_empty() {
+        return None;
+    }
+    let version = match android_env.ndk.version() {
+        Ok(version) => version.triple,
+        // `android.ndk` already reported this.
+        Err(_) => return None,
+    };
+    // A partial bound like `25.2` could be any number of packages, so that only
+    // gets pointed at the list.
+    let fix = |bound: &android::config::NdkBound| {
+        if bound.is_full() {
+            format!(
+                "Run `sdkmanager \"ndk;{}\"`, and point `NDK_HOME` at it",
+                bound
+            )
+        } else {
+            "Run `sdkmanager --list` to find an NDK in the project's range, install it, and point `NDK_HOME` at it".to_owned()
+        }
+    };
+    Some(match (range.min(), range.max()) {
+        (Some(min), _) if min.is_newer_than(version) => Item::localized(
+            Label::Error,
+            message!("android.ndk-range.too-old", version = version, min = min),
+        )
+        .with_fix(fix(min)),
+        (_, Some(max)) if max.is_older_than(version) => Item::localized(
+            Label::Error,
+            message!("android.ndk-range.too-new", version = version, max = max),
+        )
+        .with_fix(fix(max)),
+        _ => Item::localized(
+            Label::Victory,
+            message!("android.ndk-range", version = version),
+        ),
+    })
+}
+
+// An NDK copied over from another machine can have the wrong host's toolchain,
+// which only shows up once linking fails.
+fn check_ndk_host(android_env: &android::env::Env) -> Result<Item, Unrecoverable> {
+    let host_tag = android::ndk::host_tag();
+    let prebuilt = android_env.ndk.home().join("toolchains/llvm/prebuilt");
+    Ok(if prebuilt.join(host_tag).is_dir() {
+        Item::localized(Label::Info, message!("android.ndk-host", host = host_tag))
+    } else {
+        let mut present = std::fs::read_dir(&prebuilt)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.path().is_dir())
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
             })
-            .with_item(match android_env.ndk.version() {
-                Ok(ndk_version) => Ok(format!(
-                    "NDK v{} installed at {:?}",
-                    ndk_version,
-                    util::contract_home(android_env.ndk.home())?,
+            .unwrap_or_default();
+        present.sort();
+        let path = format!("{:?}", util::contract_home(android_env.ndk.home())?);
+        let message = if present.is_empty() {
+            message!("android.ndk-host.none", path = path, host = host_tag)
+        } else {
+            message!(
+                "android.ndk-host.missing",
+                path = path,
+                host = host_tag,
+                present = present.join(", "),
+            )
+        };
+        Item::localized(Label::Warning, message)
+            .with_fix("Reinstall the NDK with `sdkmanager` or Android Studio's SDK Manager")
+    })
+}
+
+// We link with the NDK's `clang` wrappers (see `generate_cargo_config`), which
+// only exist for the API levels that the NDK supports, so a `min-sdk-version`
+// outside of that range only fails once it's time to link.
+fn check_linkers(
+    android_env: &android::env::Env,
+    config: &Config,
+    opts: &CheckOpts,
+    section: Section,
+) -> Section {
+    let version_re = regex!(r"clang version (\S+)");
+    let targets = android::target::Target::all()
+        .values()
+        .filter(|target| opts.includes_target(target.triple));
+    section.with_items(targets.map(|target| {
+        match target.generate_cargo_config(config.android(), android_env) {
+            Ok(dot_cargo) => {
+                let linker = dot_cargo.linker.unwrap_or_default();
+                match util::run_and_search(
+                    &mut bossy::Command::impure(&linker).with_arg("--version"),
+                    version_re,
+                    |_, caps| caps[1].to_owned(),
+                ) {
+                    Ok(version) => Item::localized(
+                        Label::Victory,
+                        message!("android.linkers", triple = target.triple, version = version),
+                    ),
+                    Err(err) => Item::localized(
+                        Label::Error,
+                        message!(
+                            "android.linkers.broken",
+                            triple = target.triple,
+                            error = err,
+                        ),
+                    ),
+                }
+            }
+            Err(err) => Item::localized(
+                Label::Error,
+                message!("android.linkers.failed", triple = target.triple, error = err),
+            )
+            .with_fix(format!(
+                "Install an NDK that supports API level {}, or change `android.min-sdk-version` in {}",
+                config.android().min_sdk_version(),
+                crate::config::file_name()
+            )),
+        }
+    }))
+}
+
+// Describes the first way that `id` breaks the rules for application IDs, which
+// are the same as for Java package names, except that each segment has to
+// start with a letter.
+fn application_id_violation(id: &str) -> Option<Message> {
+    let quoted = format!("{:?}", id);
+    let segments = id.split('.').collect::<Vec<_>>();
+    if segments.len() < 2 {
+        return Some(message!("android.application-id.one-segment", id = quoted));
+    }
+    segments.into_iter().find_map(|segment| {
+        let quoted_segment = format!("{:?}", segment);
+        if segment.is_empty() {
+            Some(message!(
+                "android.application-id.empty-segment",
+                id = &quoted
+            ))
+        } else if !segment.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            Some(message!(
+                "android.application-id.bad-start",
+                id = &quoted,
+                segment = quoted_segment,
+            ))
+        } else if let Some(c) = segment
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && *c != '_')
+        {
+            Some(message!(
+                "android.application-id.bad-char",
+                id = &quoted,
+                segment = quoted_segment,
+                char = format!("{:?}", c),
+            ))
+        } else if domain::RESERVED_KEYWORDS.contains(&segment) {
+            Some(message!(
+                "android.application-id.keyword",
+                id = &quoted,
+                segment = quoted_segment,
+            ))
+        } else {
+            None
+        }
+    })
+}
+
+fn check_application_id(config: &Config) -> Item {
+    let id = format!(
+        "{}.{}",
+        config.app().reverse_domain(),
+        config.app().name_snake()
+    );
+    match application_id_violation(&id) {
+        Some(violation) => Item::localized(Label::Error, violation).with_fix(format!(
+            "Fix the `domain` or `name` in {}, then run `cargo mobile init`",
+            crate::config::file_name()
+        )),
+        None => Item::localized(Label::Victory, message!("android.application-id", id = id)),
+    }
+}
+
+// A missing keystore or a wrong password otherwise only shows up at the very
+// end of a release build. The password is handed to `keytool` by name, so it
+// never shows up in the command line or our output.
+fn check_release_keystore(
+    signing: &android::config::ReleaseSigning,
+) -> Result<Item, Unrecoverable> {
+    let keystore = format!("{:?}", util::contract_home(signing.keystore())?);
+    if !signing.keystore().is_file() {
+        return Ok(Item::localized(
+            Label::Error,
+            message!("android.release-keystore.missing", path = keystore),
+        )
+        .with_fix(format!(
+            "Create it with `keytool -genkeypair`, or fix `android.release-signing.keystore` in {}",
+            crate::config::file_name()
+        )));
+    }
+    let password_var = android::config::KEYSTORE_PASSWORD_VAR;
+    if std::env::var_os(password_var).is_none() {
+        return Ok(Item::localized(
+            Label::Error,
+            message!("android.release-keystore.no-password", var = password_var),
+        )
+        .with_fix(format!("Set `{}` to the keystore's password", password_var)));
+    }
+    Ok(
+        match bossy::Command::impure("keytool")
+            .with_args(&["-list", "-keystore"])
+            .with_arg(signing.keystore())
+            .with_args(&["-alias", signing.alias()])
+            .with_args(&["-storepass:env", password_var])
+            .run_and_wait_for_output()
+        {
+            Ok(_) => Item::localized(
+                Label::Victory,
+                message!(
+                    "android.release-keystore",
+                    alias = signing.alias(),
+                    path = keystore,
+                ),
+            ),
+            Err(err) => Item::localized(
+                Label::Error,
+                message!(
+                    "android.release-keystore.unreadable",
+                    alias = signing.alias(),
+                    path = keystore,
+                    error = err
+                        .stderr()
+                        .map(|stderr| String::from_utf8_lossy(stderr).trim().to_owned())
+                        .filter(|stderr| !stderr.is_empty())
+                        .unwrap_or_else(|| err.to_string()),
+                ),
+            ),
+        },
+    )
+}
+
+// Sorted oldest to newest, with anything we can't parse (i.e. release
+// candidates) sorted first.
+fn installed_build_tools(sdk_root: &Path) -> Vec<String> {
+    let mut versions = std::fs::read_dir(sdk_root.join("build-tools"))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    versions.sort_by_key(|version| VersionTriple::from_str(version).ok());
+    versions
+}
+
+fn app_build_script(config: &Config) -> Option<String> {
+    let app_dir = config.android().project_dir().join("app");
+    ["build.gradle.kts", "build.gradle"]
+        .iter()
+        .find_map(|name| std::fs::read_to_string(app_dir.join(name)).ok())
+}
+
+// Our templates don't pin this, but users are free to add it to the generated
+// build script themselves.
+fn pinned_build_tools(config: &Config) -> Option<String> {
+    app_build_script(config).and_then(|script| {
+        regex!(r#"buildToolsVersion\s*=?\s*\(?\s*"([^"]+)""#)
+            .captures(&script)
+            .map(|caps| caps[1].to_owned())
+    })
+}
+
+// What `build.gradle.kts.hbs` sets `compileSdk` to, for projects that haven't
+// been generated yet.
+const TEMPLATE_COMPILE_SDK: u32 = 31;
+
+// Groovy scripts spell it `compileSdkVersion 31`, and Kotlin ones
+// `compileSdk = 31`.
+fn compile_sdk(config: &Config) -> u32 {
+    app_build_script(config)
+        .and_then(|script| {
+            regex!(r"compileSdk(?:Version)?\s*=?\s*\(?\s*(\d+)")
+                .captures(&script)
+                .and_then(|caps| caps[1].parse().ok())
+        })
+        .unwrap_or(TEMPLATE_COMPILE_SDK)
+}
+
+fn installed_platforms(sdk_root: &Path) -> Vec<u32> {
+    let mut levels = std::fs::read_dir(sdk_root.join("platforms"))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| {
+                    entry
+                        .ok()?
+                        .file_name()
+                        .to_str()?
+                        .strip_prefix("android-")?
+                        .parse()
+                        .ok()
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    levels.sort_unstable();
+    levels
+}
+
+// Gradle needs the compile SDK's platform, and when it's missing, it fails
+// with an error that doesn't say which one.
+fn check_compile_sdk(android_env: &android::env::Env, config: &Config) -> Item {
+    let level = compile_sdk(config);
+    let installed = installed_platforms(Path::new(android_env.sdk_root()));
+    if installed.contains(&level) {
+        Item::localized(
+            Label::Victory,
+            message!("android.compile-sdk", level = level),
+        )
+    } else {
+        let message = if installed.is_empty() {
+            message!("android.compile-sdk.none", level = level)
+        } else {
+            message!(
+                "android.compile-sdk.missing",
+                level = level,
+                installed = util::list_display(
+                    &installed
+                        .iter()
+                        .map(|level| format!("android-{}", level))
+                        .collect::<Vec<_>>()
+                ),
+            )
+        };
+        Item::localized(Label::Error, message)
+            .with_fix(format!("Run `sdkmanager \"platforms;android-{}\"`", level))
+    }
+}
+
+fn check_build_tools(android_env: &android::env::Env, config: Option<&Config>) -> Item {
+    let installed = installed_build_tools(Path::new(android_env.sdk_root()));
+    match (config.and_then(pinned_build_tools), installed.last()) {
+        (Some(pinned), _) if installed.contains(&pinned) => Item::localized(
+            Label::Victory,
+            message!("android.build-tools", version = pinned),
+        ),
+        (Some(pinned), _) => {
+            let message = if installed.is_empty() {
+                message!("android.build-tools.pinned-none", version = &pinned)
+            } else {
+                message!(
+                    "android.build-tools.pinned-missing",
+                    version = &pinned,
+                    installed = util::list_display(&installed),
+                )
+            };
+            Item::localized(Label::Error, message)
+                .with_fix(format!("Run `sdkmanager \"build-tools;{}\"`", pinned))
+        }
+        (None, Some(newest)) => Item::localized(
+            Label::Info,
+            message!("android.build-tools", version = newest),
+        ),
+        (None, None) => Item::localized(Label::Error, message!("android.build-tools.none"))
+            .with_fix("Install them using the SDK Manager in Android Studio"),
+    }
+}
+
+// These are the hashes `sdkmanager` writes out once you accept the SDK license;
+// Google has changed it a few times, and any of them is good enough for Gradle.
+static SDK_LICENSE_HASHES: &[&str] = &[
+    "24333f8a63b6825ea9c5514f83c2829b004d1fee",
+    "d56f5187479451eabf01fb78af6dfcb131a6481e",
+    "8933bad161af4178b1185d1a37fbf41ea5269c55",
+];
+
+// Newer SDKs ship `sdkmanager` in `cmdline-tools`, while older ones only have
+// the deprecated `tools`. If it's in neither, it might still be on the `PATH`.
+fn sdkmanager(sdk_root: &Path) -> PathBuf {
+    let name = if cfg!(windows) {
+        "sdkmanager.bat"
+    } else {
+        "sdkmanager"
+    };
+    ["cmdline-tools/latest/bin", "tools/bin"]
+        .iter()
+        .map(|dir| sdk_root.join(dir).join(name))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+fn check_sdk_licenses(sdk_root: &Path) -> Result<Item, Unrecoverable> {
+    let license = sdk_root.join("licenses").join("android-sdk-license");
+    let accepted = std::fs::read_to_string(&license)
+        .map(|contents| {
+            contents
+                .lines()
+                .any(|line| SDK_LICENSE_HASHES.contains(&line.trim()))
+        })
+        .unwrap_or_default();
+    Ok(if accepted {
+        Item::localized(Label::Victory, message!("android.sdk-licenses"))
+    } else {
+        Item::localized(
+            Label::Error,
+            message!(
+                "android.sdk-licenses.missing",
+                path = format!("{:?}", util::contract_home(&license)?),
+            ),
+        )
+        .with_fix("Run `sdkmanager --licenses` and accept them all")
+        .with_action(FixAction::AcceptAndroidLicenses(sdkmanager(sdk_root)))
+    })
+}
+
+// System images and AVD snapshots each take a few GB, and the emulator crashes
+// in confusing ways once the disk fills up.
+const MIN_FREE_SPACE_BYTES: u64 = 5 * 1024 * 1024 * 1024;
+
+#[cfg(unix)]
+fn free_space(path: &Path) -> Result<u64, String> {
+    // `-P` keeps long device names from wrapping onto their own line.
+    let output = bossy::Command::impure_parse("df -Pk")
+        .with_arg(path)
+        .run_and_wait_for_string()
+        .map_err(|err| err.to_string())?;
+    output
+        .lines()
+        .nth(1)
+        .and_then(|line| line.split_whitespace().nth(3))
+        .ok_or_else(|| format!("Couldn't find available space in {:?}", output.trim()))?
+        .parse::<u64>()
+        .map(|kib| kib * 1024)
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(windows)]
+fn free_space(path: &Path) -> Result<u64, String> {
+    let output = bossy::Command::impure_parse("powershell -NoProfile -Command")
+        // Single-quoted strings only treat `'` specially, which is escaped by
+        // doubling it.
+        .with_arg(format!(
+            "(Get-Item '{}').PSDrive.Free",
+            path.display().to_string().replace('\'', "''")
+        ))
+        .run_and_wait_for_string()
+        .map_err(|err| err.to_string())?;
+    output.trim().parse::<u64>().map_err(|err| err.to_string())
+}
+
+fn check_free_space(sdk_root: &Path) -> Item {
+    static GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+    match free_space(sdk_root) {
+        Ok(free) if free < MIN_FREE_SPACE_BYTES => Item::localized(
+            Label::Warning,
+            message!(
+                "android.free-space.low",
+                size = format!("{:.1}", free as f64 / GIB),
+            ),
+        )
+        .with_fix(format!(
+            "Free up at least {} GB, i.e. by deleting unused system images and AVDs",
+            MIN_FREE_SPACE_BYTES / 1024 / 1024 / 1024
+        )),
+        Ok(free) => Item::localized(
+            Label::Info,
+            message!(
+                "android.free-space",
+                size = format!("{:.1}", free as f64 / GIB),
+            ),
+        ),
+        Err(err) => Item::localized(
+            Label::Warning,
+            message!("android.free-space.failed", error = err),
+        ),
+    }
+}
+
+fn accel_fix() -> &'static str {
+    if cfg!(target_os = "linux") {
+        "Enable virtualization in your BIOS/UEFI settings and make sure the `kvm` kernel module is loaded"
+    } else if cfg!(windows) {
+        "Enable \"Windows Hypervisor Platform\" in \"Turn Windows features on or off\", or install the Android Emulator Hypervisor Driver using the SDK Manager"
+    } else {
+        "Hypervisor.framework isn't available inside of most VMs, so try running on the host instead"
+    }
+}
+
+// The emulator can see that `/dev/kvm` is there, but it's not as good at
+// explaining why it can't use it.
+#[cfg(target_os = "linux")]
+fn check_kvm() -> Option<Item> {
+    let kvm = Path::new("/dev/kvm");
+    if !kvm.exists() {
+        Some(
+            Item::localized(Label::Warning, message!("android.emulator-accel.no-kvm"))
+                .with_fix(accel_fix()),
+        )
+    } else if let Err(err) = std::fs::OpenOptions::new().read(true).write(true).open(kvm) {
+        Some(
+            Item::localized(
+                Label::Warning,
+                message!("android.emulator-accel.kvm-inaccessible", error = err),
+            )
+            .with_fix("Add yourself to the `kvm` group by running `sudo usermod -aG kvm $USER`, then log out and back in"),
+        )
+    } else {
+        None
+    }
+}
+
+fn emulator_path(sdk_root: &Path) -> PathBuf {
+    sdk_root.join("emulator").join(if cfg!(windows) {
+        "emulator.exe"
+    } else {
+        "emulator"
+    })
+}
+
+// `cargo mobile android run` can only start an emulator if there's an AVD for
+// it to run.
+fn check_avds(sdk_root: &Path, section: Section) -> Section {
+    let emulator = emulator_path(sdk_root);
+    if !emulator.is_file() {
+        return section.with_item(
+            Item::localized(Label::Error, message!("android.avds.no-emulator")).with_fix(
+                "Run `sdkmanager emulator`, or install \"Android Emulator\" from Android Studio's SDK Manager",
+            ),
+        );
+    }
+    match bossy::Command::impure(&emulator)
+        .with_arg("-list-avds")
+        .run_and_wait_for_string()
+    {
+        Ok(output) => {
+            // AVD names can't contain spaces, but the emulator's log lines do.
+            let avds = output
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.contains(char::is_whitespace))
+                .collect::<Vec<_>>();
+            if avds.is_empty() {
+                section.with_item(
+                    Item::localized(Label::Warning, message!("android.avds.none")).with_fix(
+                        "Create one with `avdmanager create avd --name <name> --package <system image>`, or with Android Studio's Device Manager",
+                    ),
+                )
+            } else {
+                section.with_items(
+                    avds.into_iter().map(|avd| {
+                        Item::localized(Label::Info, message!("android.avds", name = avd))
+                    }),
+                )
+            }
+        }
+        Err(err) => section.with_item(Item::localized(
+            Label::Warning,
+            message!("android.avds.failed", error = err),
+        )),
+    }
+}
+
+// Without acceleration, the emulator is too slow to be usable, if it even runs.
+fn check_emulator_accel(sdk_root: &Path) -> Item {
+    let emulator = emulator_path(sdk_root);
+    if !emulator.is_file() {
+        return Item::localized(Label::Info, message!("android.emulator-accel.no-emulator"));
+    }
+    #[cfg(target_os = "linux")]
+    if let Some(item) = check_kvm() {
+        return item;
+    }
+    // This exits with a non-zero status when acceleration is unavailable, but
+    // either way, the explanation is on stdout between two "accel" lines.
+    let output = match std::process::Command::new(&emulator)
+        .arg("-accel-check")
+        .output()
+    {
+        Ok(output) => output,
+        Err(err) => {
+            return Item::localized(
+                Label::Warning,
+                message!("android.emulator-accel.failed", error = err),
+            )
+        }
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let details = stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty() && !line.starts_with("accel") && line.parse::<i32>().is_err()
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    if output.status.success() {
+        Item::localized(
+            Label::Info,
+            message!("android.emulator-accel", details = details),
+        )
+    } else {
+        Item::localized(
+            Label::Warning,
+            message!("android.emulator-accel.unavailable", details = details),
+        )
+        .with_fix(accel_fix())
+    }
+}
+
+fn java_version(java: &OsStr) -> Result<u32, String> {
+    let output = output_of(
+        &format!("{} -version", java.to_string_lossy()),
+        std::process::Command::new(java).arg("-version"),
+    )
+    .map_err(|err| err.to_string())?;
+    // For whatever reason, this goes to stderr.
+    let text = output.stdout_or_stderr();
+    let version = regex!(r#"version "([^"]+)""#)
+        .captures(text)
+        .map(|caps| caps[1].to_owned())
+        .ok_or_else(|| format!("Couldn't find a version in {:?}", text.trim()))?;
+    // Java 8 and older call themselves `1.x`.
+    let mut components = version.split(|c| c == '.' || c == '_' || c == '-');
+    match components.next() {
+        Some("1") => components.next(),
+        first => first,
+    }
+    .and_then(|major| major.parse().ok())
+    .ok_or_else(|| format!("Couldn't parse Java version {:?}", version))
+}
+
+fn check_java(section: Section) -> Result<Section, Unrecoverable> {
+    let path_version = java_version(OsStr::new("java"));
+    let section = section.with_item(match &path_version {
+        Ok(version) if *version < MIN_JAVA_VERSION => Item::localized(
+            Label::Warning,
+            message!(
+                "android.java.old",
+                version = version,
+                min = MIN_JAVA_VERSION
+            ),
+        )
+        .with_fix(format!(
+            "Install JDK {} or later, and put it first on your `PATH`",
+            MIN_JAVA_VERSION
+        )),
+        Ok(version) => Item::localized(Label::Victory, message!("android.java", version = version)),
+        Err(err) => Item::localized(Label::Warning, message!("android.java.failed", error = err)),
+    });
+    // Gradle prefers `JAVA_HOME`, so it might not be using the one on `PATH`.
+    Ok(match std::env::var_os("JAVA_HOME").map(PathBuf::from) {
+        Some(java_home) => match (
+            path_version,
+            java_version(java_home.join("bin/java").as_os_str()),
+        ) {
+            (Ok(path_version), Ok(home_version)) if path_version != home_version => section
+                .with_item(Item::localized(
+                    Label::Warning,
+                    message!(
+                        "android.java.home-mismatch",
+                        version = path_version,
+                        home_version = home_version,
+                        path = format!("{:?}", util::contract_home(&java_home)?),
+                    ),
                 )),
-                Err(err) => Err(format!("Failed to get NDK version: {}", err)),
-            }),
-        Err(err) => section.with_failure(err),
+            _ => section,
+        },
+        None => section,
+    })
+}
+
+// Lists the ABIs of every installed system image, which live at
+// `system-images/<api>/<tag>/<abi>`.
+#[cfg(target_os = "macos")]
+fn system_image_abis(sdk_root: &Path) -> Vec<String> {
+    fn subdirs(path: &Path) -> Vec<PathBuf> {
+        std::fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| Some(entry.ok()?.path()))
+                    .filter(|path| path.is_dir())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    let mut abis = subdirs(&sdk_root.join("system-images"))
+        .iter()
+        .flat_map(|api| subdirs(api))
+        .flat_map(|tag| subdirs(&tag))
+        .filter_map(|abi| Some(abi.file_name()?.to_str()?.to_owned()))
+        .collect::<Vec<_>>();
+    abis.sort();
+    abis.dedup();
+    abis
+}
+
+// x86 emulator images can only run on Apple Silicon through Rosetta, and
+// without it the emulator just crashes without saying why.
+#[cfg(target_os = "macos")]
+fn check_rosetta(android_env: &android::env::Env) -> Option<Item> {
+    let apple_silicon = command("sysctl -n hw.optional.arm64")
+        .map(|output| output.trim() == "1")
+        .unwrap_or_default();
+    if !apple_silicon {
+        return None;
+    }
+    let x86_abis = system_image_abis(Path::new(android_env.sdk_root()))
+        .into_iter()
+        .filter(|abi| abi == "x86" || abi == "x86_64")
+        .collect::<Vec<_>>();
+    if x86_abis.is_empty() || command("arch -x86_64 /usr/bin/true").is_ok() {
+        None
+    } else {
+        Some(
+            Item::localized(
+                Label::Warning,
+                message!("android.rosetta", abis = util::list_display(&x86_abis)),
+            )
+            .with_fix("Use an `arm64-v8a` system image instead, or install Rosetta: `softwareupdate --install-rosetta`"),
+        )
+    }
+}
+
+fn system_gradle_version() -> Option<String> {
+    command("gradle --version").ok()?.lines().find_map(|line| {
+        line.strip_prefix("Gradle ")
+            .map(|version| version.trim().to_owned())
+    })
+}
+
+// The oldest Gradle that each Android Gradle Plugin release supports, keyed by
+// AGP's major and minor version and sorted oldest to newest. See
+// https://developer.android.com/build/releases/gradle-plugin#updating-gradle
+static AGP_MIN_GRADLE: &[((u32, u32), VersionTriple)] = &[
+    ((4, 0), VersionTriple::new(6, 1, 1)),
+    ((4, 1), VersionTriple::new(6, 5, 0)),
+    ((4, 2), VersionTriple::new(6, 7, 1)),
+    ((7, 0), VersionTriple::new(7, 0, 0)),
+    ((7, 1), VersionTriple::new(7, 2, 0)),
+    ((7, 2), VersionTriple::new(7, 3, 3)),
+    ((7, 3), VersionTriple::new(7, 4, 0)),
+    ((7, 4), VersionTriple::new(7, 5, 0)),
+    ((8, 0), VersionTriple::new(8, 0, 0)),
+    ((8, 1), VersionTriple::new(8, 0, 0)),
+    ((8, 2), VersionTriple::new(8, 2, 0)),
+    ((8, 3), VersionTriple::new(8, 4, 0)),
+    ((8, 4), VersionTriple::new(8, 6, 0)),
+    ((8, 5), VersionTriple::new(8, 7, 0)),
+    ((8, 6), VersionTriple::new(8, 7, 0)),
+    ((8, 7), VersionTriple::new(8, 9, 0)),
+];
+
+// AGP releases newer than the table are held to the newest entry, which is at
+// least a lower bound.
+fn min_gradle_for_agp(agp: VersionTriple) -> Option<VersionTriple> {
+    AGP_MIN_GRADLE
+        .iter()
+        .rev()
+        .find(|((major, minor), _)| (*major, *minor) <= (agp.major, agp.minor))
+        .map(|(_, gradle)| *gradle)
+}
+
+fn wrapper_gradle_version(project_dir: &Path) -> Option<VersionTriple> {
+    let properties =
+        std::fs::read_to_string(project_dir.join("gradle/wrapper/gradle-wrapper.properties"))
+            .ok()?;
+    regex!(r"distributionUrl=.*gradle-([\d.]+)-(?:all|bin)\.zip")
+        .captures(&properties)
+        .and_then(|caps| VersionTriple::from_str(&caps[1]).ok())
+}
+
+// Our templates put AGP on the `buildscript` classpath (and on `buildSrc`'s,
+// for the Rust plugin), but newer projects tend to use the `plugins` block.
+fn agp_version(project_dir: &Path) -> Option<VersionTriple> {
+    ["", "buildSrc/"]
+        .iter()
+        .flat_map(|dir| {
+            ["build.gradle.kts", "build.gradle"]
+                .iter()
+                .map(move |name| project_dir.join(format!("{}{}", dir, name)))
+        })
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .find_map(|script| {
+            regex!(r#"com\.android\.tools\.build:gradle:([\d.]+)|id\s*\(?\s*["']com\.android\.application["']\s*\)?\s*version\s*["']([\d.]+)["']"#)
+                .captures(&script)
+                .and_then(|caps| caps.get(1).or_else(|| caps.get(2)))
+                .and_then(|version| VersionTriple::from_str(version.as_str()).ok())
+        })
+}
+
+// Gradle that's too old for AGP fails the build before it gets anywhere, with
+// an error that's easy to mistake for a problem with the project.
+fn check_gradle_agp(config: &Config) -> Option<Item> {
+    let project_dir = config.android().project_dir();
+    let (gradle, agp) = (
+        wrapper_gradle_version(&project_dir),
+        agp_version(&project_dir),
+    );
+    let (gradle, agp) = match (gradle, agp) {
+        (Some(gradle), Some(agp)) => (gradle, agp),
+        // The project hasn't been generated, which `android.gradlew` covers.
+        (None, None) => return None,
+        (Some(_), None) => {
+            return Some(Item::localized(
+                Label::Warning,
+                message!("android.gradle-agp.no-agp"),
+            ))
+        }
+        (None, Some(_)) => {
+            return Some(Item::localized(
+                Label::Warning,
+                message!("android.gradle-agp.no-gradle"),
+            ))
+        }
+    };
+    Some(match min_gradle_for_agp(agp) {
+        Some(min) if gradle < min => Item::localized(
+            Label::Error,
+            message!(
+                "android.gradle-agp.too-old",
+                agp = agp,
+                min = min,
+                gradle = gradle,
+            ),
+        )
+        .with_fix(format!(
+            "Run `./gradlew wrapper --gradle-version {}` in {:?}, or use an older Android Gradle Plugin",
+            min, project_dir
+        )),
+        Some(_) => Item::localized(
+            Label::Victory,
+            message!("android.gradle-agp", gradle = gradle, agp = agp),
+        ),
+        None => Item::localized(
+            Label::Info,
+            message!("android.gradle-agp.unknown", agp = agp, gradle = gradle),
+        ),
+    })
+}
+
+// Shell scripts that the project's Gradle tasks run only show a missing exec
+// bit as a cryptic "Permission denied" in the build log. `gradlew` itself is
+// left to `android.gradlew`.
+fn check_build_scripts(config: &Config, section: Section) -> Section {
+    let scripts = walkdir::WalkDir::new(config.android().project_dir())
+        .into_iter()
+        // Gradle's output and caches aren't part of the project.
+        .filter_entry(|entry| {
+            !matches!(
+                entry.file_name().to_str(),
+                Some("build") | Some(".gradle") | Some(".cxx")
+            )
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_type().is_file() && entry.path().extension() == Some(OsStr::new("sh"))
+        })
+        .map(|entry| entry.into_path())
+        .collect::<BTreeSet<_>>();
+    section.with_items(scripts.iter().map(|script| {
+        check_script(
+            script,
+            "Restore it, or regenerate the Android project by running `cargo mobile init`",
+        )
+    }))
+}
+
+fn check_gradlew(
+    config: &Config,
+    opts: &CheckOpts,
+    section: Section,
+) -> Result<Section, Unrecoverable> {
+    let gradlew = config.android().project_dir().join("gradlew");
+    let pretty = util::contract_home(&gradlew)?;
+    Ok(if !gradlew.is_file() {
+        let section = section.with_item(
+            Item::localized(
+                Label::Error,
+                message!("android.gradlew.missing", path = format!("{:?}", pretty)),
+            )
+            .with_fix("Regenerate the Android project by running `cargo mobile init`"),
+        );
+        // Gradle takes its sweet time starting up.
+        section.with_slow(opts.include_slow, "for a system Gradle", |section| {
+            if let Some(version) = system_gradle_version() {
+                section.with_item(Item::localized(
+                    Label::Info,
+                    message!("android.gradlew.system-gradle", version = version),
+                ))
+            } else {
+                section
+            }
+        })
+    } else if !is_executable(&gradlew) {
+        section.with_item(
+            Item::localized(
+                Label::Error,
+                message!(
+                    "android.gradlew.not-executable",
+                    path = format!("{:?}", pretty)
+                ),
+            )
+            .with_fix(format!("Run `chmod +x {}`", pretty)),
+        )
+    } else {
+        section.with_item(Item::localized(
+            Label::Victory,
+            message!("android.gradlew", path = format!("{:?}", pretty)),
+        ))
     })
 }
+
+pub fn check(
+    env: &Env,
+    config: Option<&Config>,
+    opts: &CheckOpts,
+) -> Result<Section, Unrecoverable> {
+    let section = Section::new("Android developer tools").try_with_check(
+        "android.env-paths",
+        |section| -> Result<_, Unrecoverable> {
+            ["ANDROID_SDK_ROOT", "ANDROID_HOME", "NDK_HOME"]
+                .iter()
+                .try_fold(section, |section, name| {
+                    Ok(section.with_item(check_env_path(name)?))
+                })
+        },
+    )?;
+    let section = match android::env::Env::from_env(env.clone()) {
+        Ok(android_env) => {
+            let sdk_root = Path::new(android_env.sdk_root());
+            let section = section
+                .with_item(check_sdk_root_vars(&android_env)?.with_id("android.sdk-root-vars"))
+                // It'd be a bit too inconvenient to use `map` here, since we need
+                // to use `?` within the closures...
+                .with_item(
+                    match android_env.sdk_version() {
+                        Ok(sdk_version) => Item::localized(
+                            Label::Victory,
+                            message!(
+                                "android.sdk",
+                                version = sdk_version,
+                                path = format!("{:?}", util::contract_home(sdk_root)?),
+                            ),
+                        ),
+                        Err(err) => Item::localized(
+                            Label::Error,
+                            message!("android.sdk.failed", error = err),
+                        ),
+                    }
+                    .with_id("android.sdk"),
+                )
+                .with_item(
+                    match android_env.ndk.version() {
+                        Ok(ndk_version) => Item::localized(
+                            Label::Victory,
+                            message!(
+                                "android.ndk",
+                                version = ndk_version,
+                                path =
+                                    format!("{:?}", util::contract_home(android_env.ndk.home())?),
+                            ),
+                        ),
+                        Err(err) => Item::localized(
+                            Label::Error,
+                            message!("android.ndk.failed", error = err),
+                        ),
+                    }
+                    .with_id("android.ndk"),
+                )
+                .with_items(
+                    config
+                        .zip(android_env.ndk.version().ok())
+                        .and_then(|(config, version)| {
+                            check_expected(
+                                "NDK",
+                                config.doctor().expected().ndk(),
+                                &version.to_string(),
+                            )
+                        })
+                        .map(|item| item.with_id("android.expected-ndk")),
+                )
+                .with_items(
+                    config
+                        .and_then(|config| check_ndk_range(&android_env, config))
+                        .map(|item| item.with_id("android.ndk-range")),
+                )
+                .with_item(check_ndk_host(&android_env)?.with_id("android.ndk-host"))
+                .with_check("android.linkers", |section| match config {
+                    Some(config) => check_linkers(&android_env, config, opts, section),
+                    None => section,
+                })
+                .try_with_check("android.installed-ndks", |section| {
+                    check_installed_ndks(&android_env, section)
+                })?
+                .with_item(check_build_tools(&android_env, config).with_id("android.build-tools"))
+                .with_items(config.map(|config| {
+                    check_compile_sdk(&android_env, config).with_id("android.compile-sdk")
+                }))
+                .with_item(check_sdk_licenses(sdk_root)?.with_id("android.sdk-licenses"))
+                .with_item(Item::timed(|| check_free_space(sdk_root)).with_id("android.free-space"))
+                .with_item(
+                    Item::timed(|| check_emulator_accel(sdk_root))
+                        .with_id("android.emulator-accel"),
+                )
+                .with_check("android.avds", |section| check_avds(sdk_root, section));
+            #[cfg(target_os = "macos")]
+            let section = if let Some(item) = check_rosetta(&android_env) {
+                section.with_item(item.with_id("android.rosetta"))
+            } else {
+                section
+            };
+            section
+        }
+        Err(err) => section.with_item(
+            Item::localized(Label::Error, message!("android.env.failed", error = err))
+                .with_id("android.env"),
+        ),
+    };
+    let section = section.try_with_check("android.java", check_java)?;
+    if let Some(config) = config {
+        let section =
+            section.with_item(check_application_id(config).with_id("android.application-id"));
+        let section = match config.android().release_signing() {
+            Some(signing) => section.try_with_check("android.release-keystore", |section| {
+                Ok(section.with_item(check_release_keystore(signing)?))
+            })?,
+            None => section,
+        };
+        let section = section.try_with_check("android.gradlew", |section| {
+            check_gradlew(config, opts, section)
+        })?;
+        let section = section.with_check("android.build-scripts", |section| {
+            check_build_scripts(config, section)
+        });
+        Ok(section
+            .with_items(check_gradle_agp(config).map(|item| item.with_id("android.gradle-agp"))))
+    } else {
+        Ok(section)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        agp,
+        expected,
+        case(VersionTriple::new(3, 6, 0), None),
+        case(VersionTriple::new(7, 0, 2), Some(VersionTriple::new(7, 0, 0))),
+        case(VersionTriple::new(7, 2, 1), Some(VersionTriple::new(7, 3, 3))),
+        case(VersionTriple::new(9, 0, 0), Some(VersionTriple::new(8, 9, 0)))
+    )]
+    fn test_min_gradle(agp: VersionTriple, expected: Option<VersionTriple>) {
+        assert_eq!(min_gradle_for_agp(agp), expected);
+    }
+}
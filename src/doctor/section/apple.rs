@@ -1,50 +1,456 @@
-use super::{Item, Section};
+use super::{
+    check_expected, check_script, command, command_output, command_with_timeout, message::Message,
+    stderr_suffix, Error, Item, Label, Section,
+};
 use crate::{
-    apple::{deps::xcode_plugin, system_profile::DeveloperTools, teams},
-    util::prompt,
+    apple::{self, deps::xcode_plugin, target::Target, teams},
+    config::{metadata::Metadata, Config},
+    doctor::CheckOpts,
+    os,
+    util::{self, prompt, VersionDouble, VersionTriple},
+};
+use once_cell_regex::{regex, regex_multi_line};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::Path,
+    time::Duration,
 };
-use std::path::Path;
 
-fn validate_developer_dir() -> Result<String, String> {
-    static FORBIDDEN: &str = "/Library/Developer/CommandLineTools";
-    static SUGGESTED: &str = "/Applications/Xcode.app/Contents/Developer";
+// The minimum macOS version for each Xcode release that raised it, newest
+// first. Each Xcode version needs what the first entry at or below it needs.
+const XCODE_MIN_MACOS: &[(VersionDouble, VersionTriple)] = &[
+    (VersionDouble::new(16, 0), VersionTriple::new(14, 5, 0)),
+    (VersionDouble::new(15, 3), VersionTriple::new(14, 0, 0)),
+    (VersionDouble::new(15, 0), VersionTriple::new(13, 5, 0)),
+    (VersionDouble::new(14, 3), VersionTriple::new(13, 0, 0)),
+    (VersionDouble::new(14, 0), VersionTriple::new(12, 5, 0)),
+    (VersionDouble::new(13, 3), VersionTriple::new(12, 0, 0)),
+    (VersionDouble::new(13, 0), VersionTriple::new(11, 3, 0)),
+    (VersionDouble::new(12, 5), VersionTriple::new(11, 0, 0)),
+    (VersionDouble::new(12, 0), VersionTriple::new(10, 15, 4)),
+    (VersionDouble::new(11, 4), VersionTriple::new(10, 15, 2)),
+    (VersionDouble::new(11, 0), VersionTriple::new(10, 14, 4)),
+];
+
+fn check_macos_version(xcode_version: VersionDouble) -> Option<Item> {
+    let (_, min) = XCODE_MIN_MACOS
+        .iter()
+        .find(|(xcode, _)| xcode_version >= *xcode)?;
+    let macos_version = os::Info::check()
+        .map_err(|err| err.to_string())
+        .and_then(|info| {
+            // This looks like "11.2.3 (20D91)".
+            let version = info.version.split_whitespace().next().unwrap_or_default();
+            VersionTriple::from_str(version).map_err(|err| err.to_string())
+        });
+    match macos_version {
+        Ok(macos_version) if macos_version < *min => Some(Item::localized(
+            Label::Warning,
+            message!(
+                "apple.macos-version",
+                xcode = xcode_version,
+                min = min,
+                version = macos_version,
+            ),
+        )),
+        Ok(_) => None,
+        Err(err) => Some(Item::localized(
+            Label::Warning,
+            message!("apple.macos-version.failed", error = err),
+        )),
+    }
+}
+
+static CLT_DEVELOPER_DIR: &str = "/Library/Developer/CommandLineTools";
+static XCODE_DEVELOPER_DIR: &str = "/Applications/Xcode.app/Contents/Developer";
+
+/// Offers to switch the active developer dir from the command-line tools to
+/// Xcode. This prompts, so it has to run before the sections are checked in
+/// parallel, where the prompt would interleave with their output.
+pub fn offer_xcode_developer_dir() -> Result<(), String> {
     let xcode_developer_dir = xcode_plugin::xcode_developer_dir()
         .map_err(|err| format!("Failed to get active Xcode developer dir: {}", err))?;
-    let xcode_developer_dir = {
-        if xcode_developer_dir == Path::new(FORBIDDEN) {
-            println!(
-                "Your active toolchain appears to be the Apple command-line tools: {:?}",
-                xcode_developer_dir
-            );
-            println!("Changing your active toolchain to Xcode may be necessary for everything to work correctly.");
-            let answer = loop {
-                if let Some(answer) = prompt::yes_no(
-                    format!("Would you like us to change it to {:?} for you?", SUGGESTED),
-                    Some(prompt::YesOrNo::Yes),
-                )
-                .map_err(|err| {
+    if xcode_developer_dir != Path::new(CLT_DEVELOPER_DIR) {
+        return Ok(());
+    }
+    println!(
+        "Your active toolchain appears to be the Apple command-line tools: {:?}",
+        xcode_developer_dir
+    );
+    println!("Changing your active toolchain to Xcode may be necessary for everything to work correctly.");
+    let answer = loop {
+        if let Some(answer) = prompt::yes_no(
+            format!(
+                "Would you like us to change it to {:?} for you?",
+                XCODE_DEVELOPER_DIR
+            ),
+            Some(prompt::YesOrNo::Yes),
+        )
+        .map_err(|err| {
+            format!(
+                "Failed to prompt for changing the Xcode developer dir: {}",
+                err
+            )
+        })? {
+            break answer;
+        }
+    };
+    if answer.yes() {
+        bossy::Command::impure_parse("xcode-select -s")
+            .with_arg(XCODE_DEVELOPER_DIR)
+            .run_and_wait()
+            .map_err(|err| format!("Failed to update Xcode developer dir: {}", err))?;
+    }
+    Ok(())
+}
+
+fn check_developer_dir() -> Item {
+    match xcode_plugin::xcode_developer_dir() {
+        Ok(dir) => Item::localized(
+            Label::Victory,
+            message!("apple.developer-dir", path = format!("{:?}", dir)),
+        ),
+        Err(err) => Item::localized(
+            Label::Error,
+            message!("apple.developer-dir.failed", error = err),
+        ),
+    }
+}
+
+// `cargo` can link with just the command-line tools, but simulators and
+// archiving need the real thing, and `xcodebuild` is what tells them apart.
+fn check_full_xcode() -> Item {
+    match command("xcodebuild -version") {
+        Ok(_) => Item::localized(Label::Victory, message!("apple.full-xcode")),
+        Err(err) => match xcode_plugin::xcode_developer_dir() {
+            Ok(dir) if dir == Path::new(CLT_DEVELOPER_DIR) => {
+                Item::localized(Label::Warning, message!("apple.full-xcode.clt-only")).with_fix(
                     format!(
-                        "Failed to prompt for changing the Xcode developer dir: {}",
-                        err
-                    )
-                })? {
-                    break answer;
-                }
-            };
-            if answer.yes() {
-                bossy::Command::impure_parse("xcode-select -s")
-                    .with_arg(SUGGESTED)
-                    .run_and_wait()
-                    .map_err(|err| format!("Failed to update Xcode developer dir: {}", err))?;
-                Path::new(SUGGESTED)
+                        "Install Xcode, then run `sudo xcode-select --switch {}`",
+                        XCODE_DEVELOPER_DIR
+                    ),
+                )
+            }
+            _ => Item::localized(
+                Label::Warning,
+                message!("apple.full-xcode.failed", error = err),
+            ),
+        },
+    }
+}
+
+// Apple's beta builds are numbered in the thousands within the train, i.e.
+// `15A5160n`, while releases stay in the hundreds, i.e. `15A240d`. The trailing
+// letter shows up on both, so it doesn't tell us anything.
+fn is_beta_build(build: &str) -> bool {
+    regex!(r"^\d+[A-Z](\d+)[a-z]?$")
+        .captures(build)
+        .and_then(|caps| caps[1].parse::<u32>().ok())
+        .map_or(false, |number| number >= 1000)
+}
+
+// Betas ship SDKs and toolchains with their own quirks, and it's easy to forget
+// that `xcode-select` still points at one.
+fn check_beta_xcode() -> Item {
+    let dir = match xcode_plugin::xcode_developer_dir() {
+        Ok(dir) => dir,
+        Err(err) => {
+            return Item::localized(
+                Label::Warning,
+                message!("apple.developer-dir.failed", error = err),
+            )
+        }
+    };
+    let build = command("xcodebuild -version").ok().and_then(|output| {
+        regex!(r"Build version (\S+)")
+            .captures(&output)
+            .map(|caps| caps[1].to_owned())
+    });
+    let in_beta_dir = dir.to_string_lossy().to_lowercase().contains("beta");
+    let path = format!("{:?}", dir);
+    let fix = format!(
+        "If that's not on purpose, run `sudo xcode-select --switch {}`",
+        XCODE_DEVELOPER_DIR
+    );
+    match build {
+        Some(build) if in_beta_dir || is_beta_build(&build) => Item::localized(
+            Label::Warning,
+            message!("apple.beta-xcode", path = path, build = build),
+        )
+        .with_fix(fix),
+        None if in_beta_dir => Item::localized(
+            Label::Warning,
+            message!("apple.beta-xcode.by-path", path = path),
+        )
+        .with_fix(fix),
+        Some(build) => Item::localized(
+            Label::Victory,
+            message!("apple.beta-xcode.stable", build = build),
+        ),
+        None => Item::localized(
+            Label::Victory,
+            message!("apple.beta-xcode.not-beta", path = path),
+        ),
+    }
+}
+
+// Older versions choke on projects generated by recent Xcodes.
+const MIN_COCOAPODS_VERSION: VersionTriple = VersionTriple::new(1, 11, 3);
+// `pod` is a Ruby script, so it's slow to start even when it's healthy.
+const POD_TIMEOUT: Duration = Duration::from_secs(30);
+
+// The generated project always has a `Podfile`, so whether the project uses
+// CocoaPods comes down to whether any pods are configured.
+fn uses_cocoapods(config: &Config) -> bool {
+    match Metadata::load(config.app().root_dir()) {
+        Ok(metadata) => {
+            let apple = metadata.apple();
+            [apple.ios(), apple.macos()]
+                .iter()
+                .any(|platform| !platform.pods().unwrap_or_default().is_empty())
+        }
+        Err(err) => {
+            log::info!("failed to load metadata to check for pods: {:?}", err);
+            false
+        }
+    }
+}
+
+// Describes the first way that `id` breaks the rules for bundle IDs, which only
+// allow ASCII letters, numbers, hyphens, and periods.
+fn bundle_id_violation(id: &str) -> Option<Message> {
+    let quoted = format!("{:?}", id);
+    if let Some(c) = id
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '.')
+    {
+        Some(message!(
+            "apple.bundle-id.bad-char",
+            id = quoted,
+            char = format!("{:?}", c),
+        ))
+    } else if id.starts_with('.') || id.ends_with('.') {
+        Some(message!("apple.bundle-id.edge-period", id = quoted))
+    } else if id.contains("..") {
+        Some(message!("apple.bundle-id.empty-segment", id = quoted))
+    } else {
+        None
+    }
+}
+
+fn check_bundle_id(config: &Config) -> Item {
+    let id = format!("{}.{}", config.app().reverse_domain(), config.app().name());
+    match bundle_id_violation(&id) {
+        Some(violation) => Item::localized(Label::Error, violation).with_fix(format!(
+            "Fix the `domain` or `name` in {}, then run `cargo mobile init`",
+            crate::config::file_name()
+        )),
+        None => Item::localized(Label::Victory, message!("apple.bundle-id", id = id)),
+    }
+}
+
+// Shims hand off to another Python, so when they're misconfigured, they tend to
+// break in ways that don't look like anything's wrong with Python itself.
+fn python_shim_fix(path: &str) -> Option<&'static str> {
+    if path.contains("/.pyenv/shims/") {
+        Some("Run `pyenv versions` to make sure the selected version is installed, or `pyenv global system` to use the system's")
+    } else if path.starts_with("/opt/homebrew/") || path.starts_with("/usr/local/") {
+        Some("Run `brew reinstall python`, or remove Homebrew's Python from your `PATH` to use the system's")
+    } else {
+        None
+    }
+}
+
+// Some build scripts and CocoaPods plugins run `python3`, so it needs to
+// actually be Python 3 and actually run.
+fn check_python() -> Item {
+    let path = match command("which python3") {
+        Ok(path) => path.trim().to_owned(),
+        Err(_) => {
+            return Item::localized(Label::Warning, message!("apple.python.missing"))
+                .with_fix("Run `xcode-select --install` or `brew install python`")
+        }
+    };
+    let with_shim_fix = |item: Item| match python_shim_fix(&path) {
+        Some(fix) => item.with_fix(fix),
+        None => item,
+    };
+    // Python 2 prints its version to stderr.
+    match command_output("python3 --version") {
+        Ok(output) if output.status.success() => {
+            let version = output.stdout_or_stderr().trim();
+            if version.starts_with("Python 3") {
+                Item::localized(
+                    Label::Victory,
+                    message!("apple.python", version = version, path = &path),
+                )
             } else {
-                &xcode_developer_dir
+                with_shim_fix(Item::localized(
+                    Label::Warning,
+                    message!("apple.python.not-3", version = version, path = &path),
+                ))
             }
-        } else {
-            &xcode_developer_dir
+        }
+        Ok(output) => with_shim_fix(Item::localized(
+            Label::Warning,
+            message!(
+                "apple.python.exited",
+                path = &path,
+                status = output.status,
+                stderr = stderr_suffix(&output.stderr),
+            ),
+        )),
+        Err(err) => with_shim_fix(Item::localized(
+            Label::Warning,
+            message!("apple.python.failed", path = &path, error = err),
+        )),
+    }
+}
+
+// `devicectl` shipped with Xcode 15.
+const DEVICECTL_MIN_XCODE: VersionDouble = VersionDouble::new(15, 0);
+
+// `cargo apple run` deploys with ios-deploy. Newer Xcodes come with
+// `devicectl`, which we use to check on devices but can't deploy with yet, so
+// it's worth calling out when that's all there is. `ios_deploy_version` comes
+// from `apple.ios-deploy`, which already reports ios-deploy being missing.
+fn check_deploy_backend(
+    ios_deploy_version: Option<&str>,
+    xcode_version: Option<VersionDouble>,
+) -> Item {
+    match ios_deploy_version {
+        Some(version) => Item::localized(
+            Label::Info,
+            message!("apple.deploy-backend", version = version),
+        ),
+        None if xcode_version.map_or(false, |version| version >= DEVICECTL_MIN_XCODE)
+            && command("xcrun --find devicectl").is_ok() =>
+        {
+            Item::localized(Label::Info, message!("apple.deploy-backend.devicectl-only"))
+                .with_fix("Run `brew install ios-deploy`")
+        }
+        None => Item::localized(Label::Error, message!("apple.deploy-backend.none"))
+            .with_fix("Run `brew install ios-deploy`"),
+    }
+}
+
+// These are the same defaults that project generation uses.
+pub(super) fn ios_archs(metadata: &Metadata) -> Vec<String> {
+    metadata
+        .apple()
+        .ios()
+        .valid_archs()
+        .map(<[_]>::to_vec)
+        .unwrap_or_else(|| vec!["arm64".to_owned(), "x86_64".to_owned()])
+}
+
+fn is_simulator_triple(triple: &str) -> bool {
+    triple.starts_with("x86_64-") || triple.ends_with("-sim")
+}
+
+// A project can build for devices just fine while missing the simulator's
+// target, or vice versa, so we map each of the project's archs to its target.
+// Missing targets are also failures in `cargo-mobile.targets`, but that check
+// can be skipped, and `Section::dedup` merges the two when both run.
+fn check_ios_targets(config: &Config, opts: &CheckOpts, section: Section) -> Section {
+    let metadata = super::cargo_mobile::load_metadata(config);
+    let required = super::cargo_mobile::required_targets(&metadata);
+    let missing = match super::cargo_mobile::missing_targets(required) {
+        Ok(missing) => missing,
+        Err(err) => {
+            return section.with_item(Item::localized(
+                Label::Info,
+                message!("apple.ios-targets.failed", error = err),
+            ))
         }
     };
-    Ok(format!("Active developer dir: {:?}", xcode_developer_dir))
+    let mut present = Vec::new();
+    let mut section = section;
+    for arch in ios_archs(&metadata) {
+        let target = match Target::for_arch(&arch) {
+            Some(target) => target,
+            None => {
+                section = section.with_item(Item::localized(
+                    Label::Warning,
+                    message!(
+                        "apple.ios-targets.invalid-arch",
+                        arch = format!("{:?}", arch)
+                    ),
+                ));
+                continue;
+            }
+        };
+        if !opts.includes_target(target.triple) {
+            continue;
+        }
+        let kind = if is_simulator_triple(target.triple) {
+            "simulator"
+        } else {
+            "device"
+        };
+        if missing.contains(&target.triple) {
+            section = section.with_item(
+                Item::localized(
+                    Label::Error,
+                    message!(
+                        "apple.ios-targets.missing",
+                        kind = kind,
+                        arch = arch,
+                        triple = target.triple,
+                    ),
+                )
+                .with_fix(format!("rustup target add {}", target.triple)),
+            );
+        } else {
+            present.push(format!("{} ({})", kind, target.triple));
+        }
+    }
+    if present.is_empty() {
+        section
+    } else {
+        section.with_item(Item::localized(
+            Label::Victory,
+            message!("apple.ios-targets", targets = present.join(", ")),
+        ))
+    }
+}
+
+fn check_cocoapods() -> Item {
+    match command_with_timeout("pod --version", POD_TIMEOUT) {
+        Ok(output) => match VersionTriple::from_str(output.trim()) {
+            Ok(version) if version < MIN_COCOAPODS_VERSION => Item::localized(
+                Label::Warning,
+                message!(
+                    "apple.cocoapods.old",
+                    version = version,
+                    min = MIN_COCOAPODS_VERSION,
+                ),
+            )
+            .with_fix("Run `sudo gem install cocoapods` to update it"),
+            Ok(version) => Item::localized(
+                Label::Victory,
+                message!("apple.cocoapods", version = version),
+            ),
+            Err(err) => Item::localized(
+                Label::Warning,
+                message!(
+                    "apple.cocoapods.invalid",
+                    version = format!("{:?}", output.trim()),
+                    error = err,
+                ),
+            ),
+        },
+        Err(Error::SpawnFailed { source, .. }) if source.kind() == io::ErrorKind::NotFound => {
+            Item::localized(Label::Warning, message!("apple.cocoapods.missing"))
+                .with_fix("Run `sudo gem install cocoapods` to install it")
+        }
+        Err(err) => Item::localized(
+            Label::Warning,
+            message!("apple.cocoapods.failed", error = err),
+        ),
+    }
 }
 
 fn validate_xcode_plugin(xcode_version: (u32, u32), section: Section) -> Section {
@@ -52,88 +458,585 @@ fn validate_xcode_plugin(xcode_version: (u32, u32), section: Section) -> Section
         Ok(ctx) => match ctx.check_installation() {
             Ok(status) => section
                 .with_item(if status.plugin_present {
-                    Item::victory("xcode-rust-plugin plugin present")
+                    Item::localized(Label::Victory, message!("apple.xcode-plugin.plugin"))
                 } else {
-                    Item::warning("xcode-rust-plugin plugin absent")
+                    Item::localized(Label::Warning, message!("apple.xcode-plugin.plugin.absent"))
                 })
                 .with_item(if status.lang_spec_present {
-                    Item::victory("xcode-rust-plugin lang spec present")
+                    Item::localized(Label::Victory, message!("apple.xcode-plugin.lang-spec"))
                 } else {
-                    Item::warning("xcode-rust-plugin lang spec absent")
+                    Item::localized(
+                        Label::Warning,
+                        message!("apple.xcode-plugin.lang-spec.absent"),
+                    )
                 })
                 .with_item(if status.lang_metadata_present {
-                    Item::victory("xcode-rust-plugin lang metadata present")
+                    Item::localized(Label::Victory, message!("apple.xcode-plugin.lang-metadata"))
                 } else {
-                    Item::warning("xcode-rust-plugin lang metadata absent")
+                    Item::localized(
+                        Label::Warning,
+                        message!("apple.xcode-plugin.lang-metadata.absent"),
+                    )
                 })
                 .with_item(if status.repo_fresh {
-                    Item::victory("xcode-rust-plugin is up-to-date")
+                    Item::localized(Label::Victory, message!("apple.xcode-plugin.fresh"))
                 } else {
-                    Item::warning("xcode-rust-plugin is outdated")
+                    Item::localized(Label::Warning, message!("apple.xcode-plugin.outdated"))
                 }),
-            Err(err) => section.with_failure(format!(
-                "Failed to check xcode-rust-plugin installation status: {}",
-                err
+            Err(err) => section.with_item(Item::localized(
+                Label::Error,
+                message!("apple.xcode-plugin.status-failed", error = err),
             )),
         }
         .with_item(match ctx.check_uuid() {
             Ok(status) => {
+                let uuid = format!("{:?}", status.uuid);
                 if status.supported {
-                    Item::victory(format!(
-                        "xcode-rust-plugin supports Xcode UUID {:?}",
-                        status.uuid
-                    ))
+                    Item::localized(
+                        Label::Victory,
+                        message!("apple.xcode-plugin.uuid", uuid = uuid),
+                    )
                 } else {
-                    Item::warning(format!(
-                        "xcode-rust-plugin doesn't support Xcode UUID {:?}",
-                        status.uuid
+                    Item::localized(
+                        Label::Warning,
+                        message!("apple.xcode-plugin.uuid.unsupported", uuid = uuid),
+                    )
+                }
+            }
+            Err(err) => Item::localized(
+                Label::Error,
+                message!("apple.xcode-plugin.uuid-failed", error = err),
+            ),
+        }),
+        Err(err) => section.with_item(Item::localized(
+            Label::Error,
+            message!("apple.xcode-plugin.context-failed", error = err),
+        )),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimRuntime {
+    name: String,
+    identifier: String,
+    // Older versions of Xcode give us a prose `availability` field instead.
+    is_available: Option<bool>,
+    availability: Option<String>,
+}
+
+impl SimRuntime {
+    fn is_ios(&self) -> bool {
+        self.identifier
+            .starts_with("com.apple.CoreSimulator.SimRuntime.iOS")
+    }
+
+    fn available(&self) -> bool {
+        self.is_available.unwrap_or_else(|| {
+            self.availability
+                .as_deref()
+                .map(|availability| availability == "(available)")
+                .unwrap_or_default()
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SimRuntimes {
+    runtimes: Vec<SimRuntime>,
+}
+
+fn check_simulator_runtimes(section: Section) -> Section {
+    match command("xcrun simctl list runtimes --json") {
+        Ok(json) => match serde_json::from_str::<SimRuntimes>(&json) {
+            Ok(SimRuntimes { runtimes }) => {
+                let runtimes = runtimes
+                    .into_iter()
+                    .filter(|runtime| runtime.is_ios() && runtime.available())
+                    .collect::<Vec<_>>();
+                if runtimes.is_empty() {
+                    section.with_item(Item::localized(
+                        Label::Warning,
+                        message!("apple.simulator-runtimes.none"),
                     ))
+                } else {
+                    section.with_items(runtimes.into_iter().map(|runtime| {
+                        Item::localized(
+                            Label::Info,
+                            message!("apple.simulator-runtimes", name = runtime.name),
+                        )
+                    }))
                 }
             }
-            Err(err) => Item::failure(format!(
-                "Failed to check xcode-rust-plugin UUID status: {}",
-                err
+            Err(err) => section.with_item(Item::localized(
+                Label::Warning,
+                message!("apple.simulator-runtimes.invalid", error = err),
             )),
-        }),
+        },
+        Err(err) => section.with_item(Item::localized(
+            Label::Warning,
+            message!("apple.simulator-runtimes.failed", error = err),
+        )),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimDevice {
+    name: String,
+    udid: String,
+    // Devices whose runtime has since been deleted stick around, but can't be
+    // booted.
+    is_available: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SimDevices {
+    // Keyed by runtime identifier.
+    devices: BTreeMap<String, Vec<SimDevice>>,
+}
+
+// Xcode updates tend to prune simulators, which otherwise only comes up once
+// we try to run on the missing one.
+fn check_configured_simulator(simulator: &str) -> Item {
+    let json = match command("xcrun simctl list devices --json") {
+        Ok(json) => json,
+        Err(err) => {
+            return Item::localized(
+                Label::Warning,
+                message!("apple.simulator.failed", error = err),
+            )
+        }
+    };
+    let devices = match serde_json::from_str::<SimDevices>(&json) {
+        Ok(SimDevices { devices }) => devices
+            .into_iter()
+            .filter(|(runtime, _)| runtime.starts_with("com.apple.CoreSimulator.SimRuntime.iOS"))
+            .flat_map(|(_, devices)| devices)
+            .collect::<Vec<_>>(),
+        Err(err) => {
+            return Item::localized(
+                Label::Warning,
+                message!("apple.simulator.invalid", error = err),
+            )
+        }
+    };
+    let (available, unavailable) = devices
+        .iter()
+        .partition::<Vec<_>, _>(|device| device.is_available.unwrap_or(true));
+    let matches = |device: &SimDevice| device.name == simulator || device.udid == simulator;
+    if let Some(device) = available.iter().find(|device| matches(device)) {
+        return Item::localized(
+            Label::Victory,
+            message!(
+                "apple.simulator",
+                name = format!("{:?}", device.name),
+                udid = &device.udid,
+            ),
+        );
+    }
+    let mut names = available
+        .iter()
+        .map(|device| device.name.as_str())
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    names.dedup();
+    let fix = if names.is_empty() {
+        "Create one in Xcode's \"Devices and Simulators\" window".to_owned()
+    } else {
+        format!(
+            "Set `apple.simulator` in {} to one of: {}",
+            crate::config::file_name(),
+            names.join(", ")
+        )
+    };
+    let name = format!("{:?}", simulator);
+    if unavailable.iter().any(|device| matches(device)) {
+        Item::localized(
+            Label::Warning,
+            message!("apple.simulator.unavailable", name = name),
+        )
+        .with_fix(fix)
+    } else {
+        Item::localized(
+            Label::Warning,
+            message!("apple.simulator.missing", name = name),
+        )
+        .with_fix(fix)
+    }
+}
+
+// Without `-v`, expired and revoked identities are listed too.
+fn signing_identities(valid_only: bool) -> Result<BTreeSet<String>, Error> {
+    let flags = if valid_only { " -v" } else { "" };
+    command(&format!("security find-identity{} -p codesigning", flags)).map(|output| {
+        regex_multi_line!(r#"^\s*\d+\) [0-9A-F]+ "([^"]+)""#)
+            .captures_iter(&output)
+            .map(|caps| caps[1].to_owned())
+            .collect()
+    })
+}
+
+fn check_signing_identities(section: Section) -> Section {
+    // Anything else that's valid is self-signed, which won't get you far.
+    static APPLE_ISSUED: &[&str] = &[
+        "Apple Development:",
+        "Apple Distribution:",
+        "iPhone Developer:",
+        "iPhone Distribution:",
+    ];
+    match signing_identities(true) {
+        Ok(valid) => {
+            let (apple_issued, self_signed): (Vec<_>, Vec<_>) = valid
+                .into_iter()
+                .partition(|name| APPLE_ISSUED.iter().any(|prefix| name.starts_with(prefix)));
+            if !apple_issued.is_empty() {
+                let found = if apple_issued.len() == 1 {
+                    message!("apple.signing-identities.one")
+                } else {
+                    message!("apple.signing-identities", count = apple_issued.len())
+                };
+                section
+                    .with_item(Item::localized(Label::Victory, found))
+                    .with_items(apple_issued.into_iter().map(|name| {
+                        Item::localized(
+                            Label::Info,
+                            message!("apple.signing-identities.identity", name = name),
+                        )
+                    }))
+            } else if !self_signed.is_empty()
+                || signing_identities(false).map_or(false, |all| !all.is_empty())
+            {
+                section.with_item(Item::localized(
+                    Label::Warning,
+                    message!("apple.signing-identities.unusable"),
+                ))
+            } else {
+                section.with_item(
+                    Item::localized(Label::Error, message!("apple.signing-identities.none"))
+                        .with_fix(
+                            "Sign in with your Apple ID in Xcode's Accounts preferences to get one",
+                        ),
+                )
+            }
+        }
+        Err(err) => section.with_item(Item::localized(
+            Label::Error,
+            message!("apple.signing-identities.failed", error = err),
+        )),
+    }
+}
+
+fn dir_size(path: &Path) -> Result<u64, Error> {
+    let output = bossy::Command::impure("du")
+        .with_arg("-sk")
+        .with_arg(path)
+        .run_and_wait_for_string()
+        .map_err(|source| Error::CommandFailed {
+            command: format!("du -sk {:?}", path),
+            stderr: source
+                .stderr()
+                .map(|stderr| String::from_utf8_lossy(stderr).trim().to_owned())
+                .unwrap_or_default(),
+            source,
+        })?;
+    output
+        .split_whitespace()
+        .next()
+        .and_then(|kib| kib.parse::<u64>().ok())
+        .map(|kib| kib * 1024)
+        .ok_or_else(|| Error::OutputParseFailed {
+            command: format!("du -sk {:?}", path),
+            output,
+        })
+}
+
+// DerivedData grows forever, and a big one is usually full of stale builds
+// that Xcode may pick up instead of fresh ones.
+fn check_derived_data(config: Option<&Config>, section: Section) -> Section {
+    static GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+    static DISPLAY_PATH: &str = "~/Library/Developer/Xcode/DerivedData";
+    let root = match util::home_dir() {
+        Ok(home) => home.join("Library/Developer/Xcode/DerivedData"),
         Err(err) => {
-            section.with_failure(format!("Failed to get xcode-rust-plugin context: {}", err))
+            return section.with_item(Item::localized(
+                Label::Warning,
+                message!("apple.derived-data.failed", error = err),
+            ))
         }
+    };
+    if !root.is_dir() {
+        return section;
+    }
+    let limit_gb = config
+        .map(|config| config.doctor().derived_data_limit_gb())
+        .unwrap_or_else(|| crate::doctor::config::Config::default().derived_data_limit_gb());
+    let section = section.with_item(match dir_size(&root) {
+        Ok(size) if size > limit_gb * 1024 * 1024 * 1024 => Item::localized(
+            Label::Warning,
+            message!(
+                "apple.derived-data.over-limit",
+                path = DISPLAY_PATH,
+                size = format!("{:.1}", size as f64 / GIB),
+                limit = limit_gb,
+            ),
+        )
+        .with_fix(format!(
+                "Delete {} (Xcode will rebuild what it needs), or raise `doctor.derived-data-limit-gb` in {}",
+                DISPLAY_PATH,
+                crate::config::file_name()
+        )),
+        Ok(size) => Item::localized(
+            Label::Info,
+            message!(
+                "apple.derived-data",
+                path = DISPLAY_PATH,
+                size = format!("{:.1}", size as f64 / GIB),
+            ),
+        ),
+        Err(err) => Item::localized(
+            Label::Warning,
+            message!("apple.derived-data.failed", error = err),
+        ),
+    });
+    // Xcode names each project's folder after the project plus a hash of its
+    // path, so every copy of the project gets its own.
+    let prefix = match config {
+        Some(config) => format!("{}-", config.app().name()),
+        None => return section,
+    };
+    let project_dirs = std::fs::read_dir(&root)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+                .map(|entry| entry.path())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    section.with_items(project_dirs.into_iter().filter_map(|path| {
+        let size = dir_size(&path).ok()?;
+        Some(Item::localized(
+            Label::Info,
+            message!(
+                "apple.derived-data",
+                path = format!("{}/{}", DISPLAY_PATH, path.file_name()?.to_string_lossy()),
+                size = format!("{:.1}", size as f64 / GIB),
+            ),
+        ))
+    }))
+}
+
+// Xcode runs these from the build phases, where a missing exec bit only shows
+// up as a cryptic "Permission denied" in the build log.
+fn check_build_scripts(config: &Config, section: Section) -> Section {
+    let mut scripts = BTreeSet::new();
+    if let Ok(home) = util::home_dir() {
+        scripts.insert(home.join(".cargo/bin/cargo-apple"));
+    }
+    match Metadata::load(config.app().root_dir()) {
+        Ok(metadata) => {
+            let apple = metadata.apple();
+            for platform in [apple.ios(), apple.macos()].iter() {
+                let phases = [
+                    platform.pre_build_scripts(),
+                    platform.post_compile_scripts(),
+                    platform.post_build_scripts(),
+                ];
+                scripts.extend(
+                    phases
+                        .iter()
+                        .flat_map(|phase| phase.unwrap_or_default())
+                        .filter_map(|script| script.path())
+                        .map(|path| config.apple().project_dir().join(path)),
+                );
+            }
+        }
+        Err(err) => log::info!("failed to load metadata to check build scripts: {:?}", err),
+    }
+    section.with_items(scripts.iter().map(|script| {
+        check_script(
+            script,
+            "Restore it, or remove it from the build phases in the project metadata",
+        )
+    }))
+}
+
+// The newest iOS SDK that Xcode has, since that's the one builds use.
+fn ios_sdk_version() -> Result<Option<VersionDouble>, Error> {
+    command("xcodebuild -showsdks").map(|output| {
+        regex_multi_line!(r"-sdk iphoneos(\d+)\.(\d+)")
+            .captures_iter(&output)
+            .filter_map(|caps| {
+                Some(VersionDouble::new(
+                    caps[1].parse().ok()?,
+                    caps[2].parse().ok()?,
+                ))
+            })
+            .max()
+    })
+}
+
+// A partially-updated Xcode can leave an old SDK selected, which then can't
+// build for the deployment target the project asks for.
+fn check_ios_sdk(config: &Config) -> Item {
+    let target = config.apple().ios_version();
+    match ios_sdk_version() {
+        Ok(Some(sdk)) if sdk < target => Item::localized(
+            Label::Error,
+            message!("apple.ios-sdk.too-old", sdk = sdk, target = target),
+        )
+        .with_fix(format!(
+            "Update Xcode, or lower `apple.ios-version` in {}",
+            crate::config::file_name()
+        )),
+        Ok(Some(sdk)) => Item::localized(
+            Label::Victory,
+            message!("apple.ios-sdk", sdk = sdk, target = target),
+        ),
+        Ok(None) => Item::localized(Label::Warning, message!("apple.ios-sdk.missing"))
+            .with_fix("Install the iOS platform from Xcode's \"Platforms\" settings"),
+        Err(err) => Item::localized(
+            Label::Warning,
+            message!("apple.ios-sdk.failed", error = err),
+        ),
     }
 }
 
-pub fn check() -> Section {
-    let xcode_version = DeveloperTools::new().map(|dev_tools| dev_tools.version);
+pub fn check(config: Option<&Config>, opts: &CheckOpts) -> Section {
+    let xcode_version = apple::xcode_version();
+    let mut ios_deploy_version = None;
     let section = Section::new("Apple developer tools")
         .with_item(
-            xcode_version
-                .as_ref()
-                .map(|(major, minor)| format!("Xcode v{}.{}", major, minor))
-                .map_err(|err| format!("Failed to check Xcode version: {}", err)),
+            match &xcode_version {
+                Ok(version) => {
+                    Item::localized(Label::Victory, message!("apple.xcode", version = version))
+                }
+                Err(err) => {
+                    Item::localized(Label::Error, message!("apple.xcode.failed", error = err))
+                }
+            }
+            .with_id("apple.xcode"),
         )
-        .with_item(validate_developer_dir())
+        .with_item(check_developer_dir().with_id("apple.developer-dir"))
+        .with_item(Item::timed(check_full_xcode).with_id("apple.full-xcode"))
+        .with_item(Item::timed(check_beta_xcode).with_id("apple.beta-xcode"))
+        .with_check("apple.ios-deploy", |section| {
+            let version = command("ios-deploy --version").map(|version| version.trim().to_owned());
+            ios_deploy_version = version.as_ref().ok().cloned();
+            section.with_result(version.map(|version| format!("ios-deploy v{}", version)))
+        })
         .with_item(
-            bossy::Command::impure_parse("ios-deploy --version")
-                .run_and_wait_for_str(|version| format!("ios-deploy v{}", version.trim()))
-                .map_err(|err| format!("Failed to check ios-deploy version: {}", err)),
+            Item::timed(|| {
+                check_deploy_backend(
+                    ios_deploy_version.as_deref(),
+                    xcode_version.as_ref().ok().copied(),
+                )
+            })
+            .with_id("apple.deploy-backend"),
         )
-        .with_item(
-            bossy::Command::impure_parse("xcodegen --version")
-                .run_and_wait_for_str(|version| version.trim().replace("Version: ", "XcodeGen v"))
-                .map_err(|err| format!("Failed to check ios-deploy version: {}", err)),
-        );
+        .with_check("apple.xcodegen", |section| {
+            section.with_result(
+                command("xcodegen --version")
+                    .map(|version| version.trim().replace("Version: ", "XcodeGen v")),
+            )
+        });
     let section = if let Ok(version) = xcode_version {
-        validate_xcode_plugin(version, section)
+        let section = if let Some(item) = check_macos_version(version) {
+            section.with_item(item.with_id("apple.macos-version"))
+        } else {
+            section
+        };
+        section.with_check("apple.xcode-plugin", |section| {
+            // Checking whether the plugin is up-to-date means fetching its repo.
+            section.with_slow(opts.include_slow, "xcode-rust-plugin", |section| {
+                validate_xcode_plugin((version.major, version.minor), section)
+            })
+        })
     } else {
         section
     };
-    match teams::find_development_teams() {
-        Ok(teams) => {
-            section.with_victories(teams.into_iter().map(|team| {
-                // TODO: improve development/developer consistency throughout
-                // cargo-mobile
-                format!("Development team: {} ({})", team.name, team.id)
-            }))
+    let section = section
+        .with_check("apple.simulator-runtimes", check_simulator_runtimes)
+        .with_check("apple.signing-identities", check_signing_identities);
+    let expected_xcode = match (config, &xcode_version) {
+        (Some(config), Ok(version)) => check_expected(
+            "Xcode",
+            config.doctor().expected().xcode(),
+            &version.to_string(),
+        ),
+        _ => None,
+    };
+    let section = match expected_xcode {
+        Some(item) => section.with_item(item.with_id("apple.expected-xcode")),
+        None => section,
+    };
+    let section = match config.and_then(|config| config.apple().simulator()) {
+        Some(simulator) => section.with_item(
+            Item::timed(|| check_configured_simulator(simulator)).with_id("apple.simulator"),
+        ),
+        None => section,
+    };
+    let section = match config {
+        Some(config) => section
+            .with_item(check_bundle_id(config).with_id("apple.bundle-id"))
+            .with_item(Item::timed(|| check_ios_sdk(config)).with_id("apple.ios-sdk"))
+            .with_check("apple.build-scripts", |section| {
+                check_build_scripts(config, section)
+            })
+            .with_check("apple.ios-targets", |section| {
+                check_ios_targets(config, opts, section)
+            }),
+        None => section,
+    };
+    let section = match config {
+        Some(config) if uses_cocoapods(config) => {
+            section.with_item(Item::timed(check_cocoapods).with_id("apple.cocoapods"))
         }
-        Err(err) => section.with_failure(format!("Failed to find development teams: {}", err)),
+        _ => section,
+    };
+    let section = section.with_item(Item::timed(check_python).with_id("apple.python"));
+    let section = section.with_check("apple.derived-data", |section| {
+        // Adding up a big DerivedData can take a while.
+        section.with_slow(opts.include_slow, "DerivedData size", |section| {
+            check_derived_data(config, section)
+        })
+    });
+    section.with_check("apple.teams", |section| {
+        match teams::find_development_teams() {
+            Ok(teams) => {
+                section.with_items(teams.into_iter().map(|team| {
+                    // TODO: improve development/developer consistency throughout
+                    // cargo-mobile
+                    Item::localized(
+                        Label::Victory,
+                        message!("apple.teams", name = team.name, id = team.id),
+                    )
+                }))
+            }
+            Err(err) => section.with_item(Item::localized(
+                Label::Error,
+                message!("apple.teams.failed", error = err),
+            )),
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        build,
+        expected,
+        case("15A240d", false),
+        case("15A5160n", true),
+        case("14C18", false),
+        case("14E5197f", true),
+        case("unknown", false)
+    )]
+    fn test_beta_build(build: &str, expected: bool) {
+        assert_eq!(is_beta_build(build), expected);
     }
 }
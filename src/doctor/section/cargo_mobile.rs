@@ -1,53 +1,827 @@
-use super::Section;
+use super::{check_expected, command, Error, FixAction, Item, Label, Section};
 use crate::{
-    doctor::Unrecoverable,
+    android,
+    config::{self, metadata::Metadata, Config, LoadExistingError},
+    doctor::{CheckOpts, Unrecoverable},
     os,
+    target::TargetTrait as _,
     util::{self, cli::VERSION_SHORT},
 };
+use once_cell_regex::regex;
+use std::{env::VarError, path::Path, time::Duration};
 
-fn check_os() -> Result<String, String> {
-    os::Info::check()
-        .map(|info| format!("{} v{}", info.name, info.version))
-        .map_err(|err| format!("Failed to get OS info: {}", err))
-}
-
-fn check_rust() -> Result<String, String> {
-    util::RustVersion::check()
-        .map_err(|err| err.to_string())
-        .and_then(|version| {
-            version
-                .valid()
-                .then(|| format!("rustc v{}", version.to_string()))
-                .ok_or_else(|| {
-                    format!(
-                        "iOS linking is broken on rustc v{}; please update to 1.49.0 or later",
-                        version
-                    )
-                })
+// This is the first thing anyone wants to know from a bug report, so the
+// format should stay put.
+fn check_version() -> Item {
+    match util::installed_commit_hash() {
+        Ok(Some(commit)) if !commit.is_empty() => Item::localized(
+            Label::Info,
+            message!(
+                "cargo-mobile.version",
+                version = VERSION_SHORT,
+                commit = commit
+            ),
+        ),
+        Ok(_) => Item::localized(
+            Label::Warning,
+            message!(
+                "cargo-mobile.version.unknown-commit",
+                version = VERSION_SHORT,
+                reason = "no commit hash was recorded at install time",
+            ),
+        ),
+        Err(err) => Item::localized(
+            Label::Warning,
+            message!(
+                "cargo-mobile.version.unknown-commit",
+                version = VERSION_SHORT,
+                reason = err,
+            ),
+        ),
+    }
+}
+
+fn check_os() -> Item {
+    match os::Info::check() {
+        Ok(info) => Item::localized(
+            Label::Victory,
+            message!("cargo-mobile.os", name = info.name, version = info.version),
+        ),
+        Err(err) => Item::localized(
+            Label::Error,
+            message!("cargo-mobile.os.failed", error = err),
+        ),
+    }
+}
+
+fn check_rust() -> Item {
+    match util::RustVersion::check() {
+        Ok(version) if version.valid() => Item::localized(
+            Label::Victory,
+            message!("cargo-mobile.rustc", version = version),
+        ),
+        Ok(version) => Item::localized(
+            Label::Error,
+            message!("cargo-mobile.rustc.link-broken", version = version),
+        )
+        .with_fix(format!(
+            "Update to {} or later: `rustup update stable && rustup default stable`",
+            util::LINK_BUG_FIRST_FIXED
+        )),
+        Err(err) => Item::failure(err),
+    }
+}
+
+// The channel pinned by the project's `rust-toolchain.toml` (or the older
+// `rust-toolchain`, which can also just hold the channel name), if any.
+fn pinned_channel(root: &Path) -> Option<Result<String, String>> {
+    let (path, contents) = ["rust-toolchain.toml", "rust-toolchain"]
+        .iter()
+        .map(|name| root.join(name))
+        .find_map(|path| {
+            std::fs::read_to_string(&path)
+                .ok()
+                .map(|contents| (path, contents))
+        })?;
+    Some(match contents.parse::<toml::Value>() {
+        Ok(value) => value
+            .get("toolchain")
+            .and_then(|toolchain| toolchain.get("channel"))
+            .and_then(toml::Value::as_str)
+            .map(str::to_owned)
+            .ok_or_else(|| format!("{:?} doesn't set `toolchain.channel`", path)),
+        Err(_) if !contents.trim().is_empty() && !contents.trim().contains(char::is_whitespace) => {
+            Ok(contents.trim().to_owned())
+        }
+        Err(err) => Err(format!("Failed to parse {:?}: {}", path, err)),
+    })
+}
+
+// `None` means the channel is something we don't know how to compare against,
+// i.e. a custom toolchain name. Nightly and beta dates aren't compared, since
+// `rustc` reports its commit date, which is usually the day before.
+fn channel_matches(channel: &str, version: &util::RustVersion) -> Option<bool> {
+    let flavor = version.flavor.as_ref().map(|flavor| flavor.flavor.as_str());
+    let channel = channel.split('-').next().unwrap_or(channel);
+    match channel {
+        "stable" => Some(flavor.is_none()),
+        "beta" | "nightly" => Some(flavor == Some(channel)),
+        _ if channel.starts_with(|c: char| c.is_ascii_digit()) => {
+            let triple = version.triple.to_string();
+            Some(
+                flavor.is_none()
+                    && (triple == channel || triple.starts_with(&format!("{}.", channel))),
+            )
+        }
+        _ => None,
+    }
+}
+
+// `rustup` resolves the toolchain by searching upward from the current
+// directory, just like we do to find the project, so the `rustc` checked above
+// is already the project's toolchain... unless `RUSTUP_TOOLCHAIN` or a
+// `rustup override` is taking precedence over the pinned one.
+fn check_toolchain(config: &Config) -> Option<Item> {
+    let channel = match pinned_channel(config.app().root_dir())? {
+        Ok(channel) => channel,
+        Err(err) => return Some(Item::warning(err)),
+    };
+    let version = match util::RustVersion::check() {
+        Ok(version) => version,
+        // `check_rust` already reports this.
+        Err(_) => return None,
+    };
+    Some(match channel_matches(&channel, &version) {
+        Some(true) => Item::localized(
+            Label::Victory,
+            message!("cargo-mobile.toolchain.matches", channel = channel),
+        ),
+        Some(false) => Item::localized(
+            Label::Warning,
+            message!(
+                "cargo-mobile.toolchain.mismatch",
+                channel = channel,
+                version = version
+            ),
+        )
+        .with_fix("Unset `RUSTUP_TOOLCHAIN` and run `rustup override unset` in the project"),
+        None => Item::localized(
+            Label::Info,
+            message!("cargo-mobile.toolchain.unknown", channel = channel),
+        ),
+    })
+}
+
+// Plenty of tools only print UTF-8 when the locale says they can, and anything
+// else makes `bossy` fail with `InvalidUtf8` on their output. The first of
+// these that's set (and non-empty) wins, which is how libc resolves it too.
+#[cfg(unix)]
+fn check_locale() -> Item {
+    let effective = ["LC_ALL", "LC_CTYPE", "LANG"].iter().find_map(|name| {
+        std::env::var(name)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| (*name, value))
+    });
+    match effective {
+        Some((name, value)) => {
+            let lower = value.to_ascii_lowercase();
+            if lower.contains("utf-8") || lower.contains("utf8") {
+                Item::localized(
+                    Label::Victory,
+                    message!("cargo-mobile.locale.utf8", name = name, value = value),
+                )
+            } else {
+                Item::localized(
+                    Label::Warning,
+                    message!("cargo-mobile.locale.not-utf8", name = name, value = value),
+                )
+                .with_fix(format!(
+                    "Set `{}` to a UTF-8 locale, i.e. `en_US.UTF-8`",
+                    name
+                ))
+            }
+        }
+        None => Item::localized(Label::Warning, message!("cargo-mobile.locale.unset"))
+            .with_fix("Set `LANG` to a UTF-8 locale, i.e. `en_US.UTF-8`"),
+    }
+}
+
+// Windows failures are much easier to make sense of when we know which shell
+// they came from. `cmd` doesn't announce itself, so it's the fallback.
+#[cfg(windows)]
+fn check_shell() -> Item {
+    let shell = if let Ok(msystem) = std::env::var("MSYSTEM") {
+        format!("MSYS2/Git Bash ({})", msystem)
+    } else if std::env::var_os("PSModulePath").is_some() {
+        "PowerShell".to_owned()
+    } else {
+        std::env::var("ComSpec").unwrap_or_else(|_| "cmd.exe".to_owned())
+    };
+    Item::localized(Label::Info, message!("cargo-mobile.shell", shell = shell))
+}
+
+#[cfg(not(windows))]
+fn check_shell() -> Item {
+    match std::env::var("SHELL") {
+        Ok(shell) if !shell.is_empty() => {
+            Item::localized(Label::Info, message!("cargo-mobile.shell", shell = shell))
+        }
+        _ => Item::localized(Label::Info, message!("cargo-mobile.shell.unknown")),
+    }
+}
+
+fn check_config(config: &Result<Option<Config>, LoadExistingError>) -> Result<Item, Unrecoverable> {
+    Ok(match config {
+        Ok(Some(config)) => Item::localized(
+            Label::Info,
+            message!(
+                "cargo-mobile.config.found",
+                path = format!("{:?}", util::contract_home(config.path())?),
+            ),
+        ),
+        Ok(None) => Item::localized(Label::Info, message!("cargo-mobile.config.absent")),
+        Err(err) => Item::failure(err.report()),
+    })
+}
+
+// Projects generated before we started recording the config's hash don't have
+// one, so for those we fall back to comparing modification times.
+fn check_gen_freshness(config: &Config, project_dir: &Path) -> Result<Option<Item>, Unrecoverable> {
+    if !project_dir.is_dir() {
+        return Ok(None);
+    }
+    let stale = match std::fs::read_to_string(Config::gen_hash_path(project_dir)) {
+        Ok(recorded) => config
+            .contents_hash()
+            .map(|current| recorded.trim() != current)
+            .unwrap_or_default(),
+        Err(_) => {
+            let modified = |path: &Path| {
+                path.metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            };
+            match (modified(&config.path()), modified(project_dir)) {
+                (Some(config_modified), Some(gen_modified)) => config_modified > gen_modified,
+                _ => false,
+            }
+        }
+    };
+    let path = format!("{:?}", util::contract_home(project_dir)?);
+    Ok(Some(if stale {
+        Item::localized(
+            Label::Warning,
+            message!("cargo-mobile.gen-freshness.stale", path = path),
+        )
+        .with_fix("Run `cargo mobile init` to regenerate it")
+    } else {
+        Item::localized(
+            Label::Victory,
+            message!("cargo-mobile.gen-freshness.fresh", path = path),
+        )
+    }))
+}
+
+fn check_template_version(project_dir: &Path) -> Result<Option<Item>, Unrecoverable> {
+    if !project_dir.is_dir() {
+        return Ok(None);
+    }
+    let path = format!("{:?}", util::contract_home(project_dir)?);
+    let expected = config::TEMPLATE_VERSION;
+    Ok(Some(match Config::read_template_version(project_dir) {
+        Some(version) if version >= expected => Item::localized(
+            Label::Victory,
+            message!(
+                "cargo-mobile.template-version.current",
+                path = path,
+                version = version,
+            ),
+        ),
+        Some(version) => Item::localized(
+            Label::Warning,
+            message!(
+                "cargo-mobile.template-version.old",
+                path = path,
+                version = version,
+                expected = expected,
+            ),
+        )
+        .with_fix("Run `cargo mobile init` to regenerate it"),
+        None => Item::localized(
+            Label::Warning,
+            message!(
+                "cargo-mobile.template-version.missing",
+                path = path,
+                expected = expected,
+            ),
+        )
+        .with_fix("Run `cargo mobile init` to regenerate it"),
+    }))
+}
+
+// Going by the name is crude, but it's better than printing somebody's API key
+// into a bug report.
+fn looks_secret(name: &str) -> bool {
+    static SECRET_WORDS: &[&str] = &[
+        "TOKEN",
+        "SECRET",
+        "PASSWORD",
+        "PASSWD",
+        "KEY",
+        "CREDENTIAL",
+        "AUTH",
+    ];
+    let name = name.to_ascii_uppercase();
+    SECRET_WORDS.iter().any(|word| name.contains(word))
+}
+
+fn check_required_env(config: &Config) -> Vec<Item> {
+    config
+        .doctor()
+        .required_env()
+        .iter()
+        .map(|name| match std::env::var(name) {
+            Ok(value) => {
+                let value = if looks_secret(name) {
+                    "<redacted>".to_owned()
+                } else {
+                    format!("{:?}", value)
+                };
+                Item::localized(
+                    Label::Victory,
+                    message!("cargo-mobile.required-env.set", name = name, value = value),
+                )
+            }
+            // Cargo exports these to builds on its own, via `.cargo/config`.
+            Err(VarError::NotPresent)
+                if config
+                    .env()
+                    .as_ref()
+                    .map_or(false, |env| env.contains_key(name)) =>
+            {
+                Item::localized(
+                    Label::Victory,
+                    message!("cargo-mobile.required-env.set-by-config", name = name),
+                )
+            }
+            Err(VarError::NotPresent) => Item::localized(
+                Label::Warning,
+                message!("cargo-mobile.required-env.missing", name = name),
+            )
+            .with_fix(format!(
+                "Export `{}` in your shell profile, or add it to the `env` table in {}",
+                name,
+                config::file_name()
+            )),
+            Err(VarError::NotUnicode(_)) => Item::localized(
+                Label::Warning,
+                message!("cargo-mobile.required-env.not-unicode", name = name),
+            ),
         })
+        .collect()
 }
 
-pub fn check() -> Result<Section, Unrecoverable> {
-    let section = Section::new(format!("cargo-mobile {}", VERSION_SHORT));
-    Ok(match util::install_dir() {
-        Ok(install_dir) => section
-            .with_item(util::installed_commit_msg().map(|msg| {
-                msg.map(util::format_commit_msg)
-                    .unwrap_or_else(|| "Installed commit message isn't present".to_string())
-            }))
-            .with_item(if install_dir.exists() {
-                Ok(format!(
-                    "Installed at {:?}",
-                    util::contract_home(&install_dir)?,
+// Tools that are commonly installed more than once, i.e. by both a package
+// manager and an SDK.
+static PATH_TOOLS: &[&str] = &[
+    "adb",
+    #[cfg(target_os = "macos")]
+    "xcrun",
+    "cargo",
+];
+
+fn path_matches(tool: &str) -> Result<Vec<String>, util::RunAndSearchError> {
+    #[cfg(not(windows))]
+    let mut command = bossy::Command::impure("which").with_args(&["-a", tool]);
+    #[cfg(windows)]
+    let mut command = bossy::Command::impure("where").with_arg(tool);
+    util::run_and_search(&mut command, regex!(r"(?m)^\s*\S.*$"), |output, _| {
+        let mut matches = Vec::<String>::new();
+        for line in output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            // The same directory can be on PATH more than once, which isn't
+            // worth warning about.
+            if !matches.iter().any(|found| found == line) {
+                matches.push(line.to_owned());
+            }
+        }
+        matches
+    })
+}
+
+// The first match on PATH is the one that actually runs, so if there's more
+// than one, updating the wrong one looks like it did nothing.
+fn check_path_tool(tool: &str) -> Option<Item> {
+    // If the tool isn't on PATH at all, the section that needs it says so.
+    let matches = path_matches(tool).ok()?;
+    let (first, others) = matches.split_first()?;
+    Some(if others.is_empty() {
+        Item::localized(
+            Label::Victory,
+            message!("cargo-mobile.path-tools.unique", tool = tool, path = first),
+        )
+    } else {
+        Item::localized(
+            Label::Warning,
+            message!(
+                "cargo-mobile.path-tools.shadowed",
+                count = matches.len(),
+                tool = tool,
+                path = first,
+                others = others.join(", "),
+            ),
+        )
+        .with_fix(
+            "Remove the copies you don't use, or reorder PATH so that the one you want comes first",
+        )
+    })
+}
+
+// These are the same targets that `init` installs.
+pub(crate) fn all_targets() -> Vec<&'static str> {
+    let targets = android::target::Target::all()
+        .values()
+        .map(|target| target.triple);
+    #[cfg(target_os = "macos")]
+    let targets = targets.chain(
+        crate::apple::target::Target::all()
+            .values()
+            .map(|target| target.triple),
+    );
+    targets.collect()
+}
+
+// A project without metadata builds for everything, which is also what project
+// generation falls back to.
+pub(crate) fn load_metadata(config: &Config) -> Metadata {
+    Metadata::load(config.app().root_dir())
+        .map_err(|err| log::info!("failed to load metadata to check targets: {:?}", err))
+        .unwrap_or_default()
+}
+
+// Only the targets that the project actually builds for, so platforms marked as
+// unsupported are left out, and iOS only needs the targets for its archs.
+pub(crate) fn required_targets(metadata: &Metadata) -> Vec<&'static str> {
+    let mut targets = Vec::new();
+    if metadata.android().supported() {
+        targets.extend(
+            android::target::Target::all()
+                .values()
+                .map(|target| target.triple),
+        );
+    }
+    #[cfg(target_os = "macos")]
+    if metadata.apple().supported() {
+        targets.extend(
+            super::apple::ios_archs(metadata)
+                .iter()
+                .filter_map(|arch| crate::apple::target::Target::for_arch(arch))
+                .map(|target| target.triple),
+        );
+    }
+    targets
+}
+
+fn installed_targets() -> Result<Vec<String>, Error> {
+    command("rustup target list --installed").map(|installed| {
+        installed
+            .lines()
+            .map(|triple| triple.trim().to_owned())
+            .collect()
+    })
+}
+
+/// The targets out of `required` that aren't installed.
+pub(crate) fn missing_targets(required: Vec<&'static str>) -> Result<Vec<&'static str>, Error> {
+    let installed = installed_targets()?;
+    Ok(required
+        .into_iter()
+        .filter(|triple| !installed.iter().any(|installed| installed == triple))
+        .collect())
+}
+
+fn check_targets(config: &Config, opts: &CheckOpts, section: Section) -> Section {
+    match installed_targets() {
+        Ok(installed) => {
+            let targets = required_targets(&load_metadata(config))
+                .into_iter()
+                .filter(|triple| opts.includes_target(triple))
+                .map(|triple| {
+                    if installed.iter().any(|installed| installed == triple) {
+                        Item::localized(
+                            Label::Victory,
+                            message!("cargo-mobile.targets.installed", triple = triple),
+                        )
+                    } else {
+                        Item::localized(
+                            Label::Error,
+                            message!("cargo-mobile.targets.missing", triple = triple),
+                        )
+                        .with_fix(format!("Run `rustup target add {}`", triple))
+                        .with_action(FixAction::AddRustTarget(triple.to_owned()))
+                    }
+                })
+                .collect();
+            section.with_item(
+                Item::group(message!("cargo-mobile.targets"), targets)
+                    .with_id("cargo-mobile.targets"),
+            )
+        }
+        Err(err) => section.with_item(
+            Item::localized(
+                Label::Error,
+                message!("cargo-mobile.targets.failed", error = err),
+            )
+            .with_id("cargo-mobile.targets"),
+        ),
+    }
+}
+
+// The hosts that `cargo`, Gradle, and CocoaPods download from. Any URL on each
+// host works, since we only care whether the host can be reached at all.
+static MIRRORS: &[(&str, &str)] = &[
+    ("crates.io", "https://index.crates.io/config.json"),
+    (
+        "Google Maven",
+        "https://dl.google.com/dl/android/maven2/index.html",
+    ),
+    ("Maven Central", "https://repo.maven.apache.org/maven2/"),
+    ("Gradle", "https://services.gradle.org/distributions/"),
+    #[cfg(target_os = "macos")]
+    ("CocoaPods", "https://cdn.cocoapods.org/"),
+];
+
+// Corporate proxies are the usual culprit when dependency downloads fail, and
+// the tools themselves are rarely clear about it.
+fn check_mirrors(timeout: Duration, section: Section) -> Section {
+    let proxy = ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|proxy| !proxy.is_empty()));
+    let mut agent = ureq::AgentBuilder::new().timeout(timeout);
+    let mut section = section;
+    if let Some(proxy) = &proxy {
+        match ureq::Proxy::new(proxy) {
+            Ok(parsed) => {
+                agent = agent.proxy(parsed);
+                section = section.with_item(Item::localized(
+                    Label::Info,
+                    message!("cargo-mobile.mirrors.proxy", proxy = proxy),
+                ));
+            }
+            Err(err) => {
+                return section.with_item(Item::localized(
+                    Label::Warning,
+                    message!(
+                        "cargo-mobile.mirrors.proxy-invalid",
+                        proxy = format!("{:?}", proxy),
+                        error = err,
+                    ),
                 ))
+            }
+        }
+    }
+    let agent = agent.build();
+    section.with_items(MIRRORS.iter().map(|(name, url)| {
+        match agent.head(url).call() {
+            // Plenty of servers don't bother with `HEAD`, but answering at all
+            // means nothing is in the way.
+            Ok(_) | Err(ureq::Error::Status(404 | 405, _)) => Item::localized(
+                Label::Victory,
+                message!("cargo-mobile.mirrors.reachable", name = name),
+            ),
+            Err(ureq::Error::Status(status, _)) => Item::localized(
+                Label::Warning,
+                message!(
+                    "cargo-mobile.mirrors.status",
+                    name = name,
+                    url = url,
+                    status = status,
+                ),
+            ),
+            Err(err) => Item::localized(
+                Label::Warning,
+                message!(
+                    "cargo-mobile.mirrors.unreachable",
+                    name = name,
+                    url = url,
+                    error = err,
+                ),
+            ),
+        }
+    }))
+}
+
+// Whether each package is installed, according to the platform's package
+// manager. `None` if there's no package manager we know how to ask.
+#[cfg(target_os = "macos")]
+fn package_status(packages: &[String]) -> Option<(&'static str, Result<Vec<bool>, Error>)> {
+    // Asking about them all at once would fail as soon as one is missing.
+    let installed = command("brew list -1").map(|output| {
+        let installed = output.lines().map(str::trim).collect::<Vec<_>>();
+        packages
+            .iter()
+            .map(|package| installed.contains(&package.as_str()))
+            .collect()
+    });
+    Some(("brew install", installed))
+}
+
+#[cfg(target_os = "linux")]
+fn package_status(packages: &[String]) -> Option<(&'static str, Result<Vec<bool>, Error>)> {
+    // `dpkg -s` exits non-zero for packages that aren't installed, so we have
+    // to ask about them one at a time. Not having `dpkg` at all is the only
+    // real failure.
+    let installed = command("dpkg --version").map(|_| {
+        packages
+            .iter()
+            .map(|package| command(&format!("dpkg -s {}", package)).is_ok())
+            .collect()
+    });
+    Some(("sudo apt install", installed))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn package_status(_packages: &[String]) -> Option<(&'static str, Result<Vec<bool>, Error>)> {
+    None
+}
+
+fn required_packages(config: &Config) -> &[String] {
+    let required = config.doctor().required_packages();
+    if cfg!(target_os = "macos") {
+        required.brew()
+    } else {
+        required.apt()
+    }
+}
+
+// Minimal CI images tend to be missing a library or two that some tool quietly
+// depends on.
+fn check_packages(packages: &[String], section: Section) -> Section {
+    match package_status(packages) {
+        Some((install, Ok(installed))) => {
+            let (present, missing) = packages
+                .iter()
+                .zip(installed)
+                .partition::<Vec<_>, _>(|(_, installed)| *installed);
+            let section = section.with_items(present.into_iter().map(|(package, _)| {
+                Item::localized(
+                    Label::Victory,
+                    message!("cargo-mobile.packages.installed", package = package),
+                )
+            }));
+            if missing.is_empty() {
+                section
             } else {
-                Err(format!(
-                    "The cargo-mobile installation directory is missing! Checked at {:?}",
-                    install_dir,
-                ))
+                let missing = missing
+                    .into_iter()
+                    .map(|(package, _)| package.as_str())
+                    .collect::<Vec<_>>();
+                section.with_item(
+                    Item::localized(
+                        Label::Warning,
+                        message!(
+                            "cargo-mobile.packages.missing",
+                            packages = missing.join(", ")
+                        ),
+                    )
+                    .with_fix(format!(
+                        "Run `{} {}`",
+                        install,
+                        missing.join(" ")
+                    )),
+                )
+            }
+        }
+        Some((_, Err(err))) => section.with_item(Item::localized(
+            Label::Warning,
+            message!("cargo-mobile.packages.failed", error = err),
+        )),
+        None => section,
+    }
+}
+
+pub fn check(
+    config: &Result<Option<Config>, LoadExistingError>,
+    opts: &CheckOpts,
+) -> Result<Section, Unrecoverable> {
+    let section = Section::new(format!("cargo-mobile {}", VERSION_SHORT))
+        .with_item(check_version().with_id("cargo-mobile.version"));
+    let section = match util::install_dir() {
+        Ok(install_dir) => section
+            .with_item(
+                Item::from(util::installed_commit_msg().map(|msg| {
+                    msg.map(util::format_commit_msg)
+                        .unwrap_or_else(|| "Installed commit message isn't present".to_string())
+                }))
+                .with_id("cargo-mobile.commit"),
+            )
+            .with_item(
+                if install_dir.exists() {
+                    Item::localized(
+                        Label::Victory,
+                        message!(
+                            "cargo-mobile.install-dir.found",
+                            path = format!("{:?}", util::contract_home(&install_dir)?),
+                        ),
+                    )
+                } else {
+                    Item::localized(
+                        Label::Error,
+                        message!(
+                            "cargo-mobile.install-dir.missing",
+                            path = format!("{:?}", install_dir),
+                        ),
+                    )
+                }
+                .with_id("cargo-mobile.install-dir"),
+            ),
+        Err(err) => section.with_item(Item::failure(err).with_id("cargo-mobile.install-dir")),
+    }
+    .with_item(Item::timed(check_os).with_id("cargo-mobile.os"))
+    .with_item(Item::timed(check_rust).with_id("cargo-mobile.rust"))
+    .with_item(check_shell().with_id("cargo-mobile.shell"));
+    // Windows doesn't use these variables at all.
+    #[cfg(unix)]
+    let section = section.with_item(check_locale().with_id("cargo-mobile.locale"));
+    let section = section
+        .with_item(check_config(config)?.with_id("cargo-mobile.config"))
+        .with_check("cargo-mobile.path-tools", |section| {
+            section.with_items(PATH_TOOLS.iter().filter_map(|tool| check_path_tool(tool)))
+        })
+        .with_check("cargo-mobile.mirrors", |section| {
+            section.with_slow(opts.include_slow, "package mirrors", |section| {
+                check_mirrors(opts.timeout, section)
+            })
+        });
+    // Outside of a project, there's nothing to build, so missing targets don't
+    // matter yet.
+    Ok(if let Ok(Some(config)) = config {
+        let project_dirs = vec![
+            #[cfg(target_os = "macos")]
+            config.apple().project_dir(),
+            config.android().project_dir(),
+        ];
+        let section = match check_toolchain(config) {
+            Some(item) => section.with_item(item.with_id("cargo-mobile.toolchain")),
+            None => section,
+        };
+        let expected_rust = util::RustVersion::check().ok().and_then(|version| {
+            check_expected(
+                "rustc",
+                config.doctor().expected().rust(),
+                &version.triple.to_string(),
+            )
+        });
+        let section = match expected_rust {
+            Some(item) => section.with_item(item.with_id("cargo-mobile.expected-rust")),
+            None => section,
+        };
+        let mut section = section
+            .with_check("cargo-mobile.targets", |section| {
+                check_targets(config, opts, section)
+            })
+            .with_check("cargo-mobile.packages", |section| {
+                match required_packages(config) {
+                    [] => section,
+                    // Package databases can be slow to query.
+                    packages => {
+                        section.with_slow(opts.include_slow, "system packages", |section| {
+                            check_packages(packages, section)
+                        })
+                    }
+                }
+            })
+            .with_items(
+                check_required_env(config)
+                    .into_iter()
+                    .map(|item| item.with_id("cargo-mobile.required-env")),
+            );
+        for project_dir in project_dirs {
+            if let Some(item) = check_gen_freshness(config, &project_dir)? {
+                section = section.with_item(item.with_id("cargo-mobile.gen-freshness"));
+            }
+            if let Some(item) = check_template_version(&project_dir)? {
+                section = section.with_item(item.with_id("cargo-mobile.template-version"));
+            }
+        }
+        section
+    } else {
+        section
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::{RustVersion, RustVersionFlavor, VersionTriple};
+    use rstest::rstest;
+
+    #[rstest(
+        channel,
+        flavor,
+        expected,
+        case("stable", None, Some(true)),
+        case("stable", Some("nightly"), Some(false)),
+        case("nightly-2023-01-01", Some("nightly"), Some(true)),
+        case("1.70", None, Some(true)),
+        case("1.70.0", None, Some(true)),
+        case("1.7", None, Some(false)),
+        case("1.69.0", None, Some(false)),
+        case("my-custom-toolchain", None, None)
+    )]
+    fn test_channel_matches(channel: &str, flavor: Option<&str>, expected: Option<bool>) {
+        let version = RustVersion {
+            triple: VersionTriple::new(1, 70, 0),
+            flavor: flavor.map(|flavor| RustVersionFlavor {
+                flavor: flavor.to_owned(),
+                candidate: None,
             }),
-        Err(err) => section.with_failure(err),
+            details: None,
+        };
+        assert_eq!(channel_matches(channel, &version), expected);
     }
-    .with_item(check_os())
-    .with_item(check_rust()))
 }
@@ -1,33 +1,528 @@
-use super::Section;
+use super::{command_with_retries, command_with_timeout, Error, Item, Label, Section};
 use crate::{
     android::{self, adb},
+    config::Config,
+    doctor::CheckOpts,
     env::Env,
 };
+use serde::Serialize;
+use std::{io, time::Duration};
 
-pub fn check(env: &Env) -> Section {
-    let section = Section::new("Connected devices");
+// The first `adb devices` also starts the `adb` server, which can fail while a
+// device is still being plugged in.
+const ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(300);
+
+// This is `adb::get_prop`, but with a timeout.
+fn get_prop(serial_no: &str, prop: &str, timeout: Duration) -> Result<String, Error> {
+    command_with_timeout(
+        &format!("adb -s {} shell getprop {}", serial_no, prop),
+        timeout,
+    )
+    .map(|value| value.trim().to_owned())
+}
+
+// `adb::device_list` also looks up the Bluetooth name, which takes another
+// command per device, so we settle for the model.
+fn describe_android_device(serial_no: &str, timeout: Duration) -> String {
+    match get_prop(serial_no, "ro.product.model", timeout) {
+        Ok(model) if !model.is_empty() => format!("{} ({})", model, serial_no),
+        _ => serial_no.to_owned(),
+    }
+}
+
+fn check_api_level(device: &str, serial_no: &str, min: u32, timeout: Duration) -> Item {
+    match get_prop(serial_no, "ro.build.version.sdk", timeout)
+        .map_err(|err| err.to_string())
+        .and_then(|level| level.parse::<u32>().map_err(|err| err.to_string()))
+    {
+        Ok(level) if level < min => Item::localized(
+            Label::Warning,
+            message!(
+                "device-list.android.api-level.low",
+                device = device,
+                level = level,
+                min = min,
+            ),
+        ),
+        Ok(_) => Item::localized(
+            Label::Victory,
+            message!("device-list.device", device = device),
+        ),
+        Err(err) => Item::localized(
+            Label::Warning,
+            message!(
+                "device-list.android.api-level.failed",
+                device = device,
+                error = err,
+            ),
+        ),
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Android,
+    Ios,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Connection {
+    Usb,
+    Network,
+    Emulator,
+}
+
+impl Connection {
+    // `adb` doesn't tell us this directly, but the serial number format does.
+    fn from_android_serial_no(serial_no: &str) -> Self {
+        if serial_no.starts_with("emulator-") {
+            Self::Emulator
+        } else if serial_no.contains(':') {
+            Self::Network
+        } else {
+            Self::Usb
+        }
+    }
+}
+
+/// A connected device, minus everything that only makes sense in the report.
+#[derive(Debug, Serialize)]
+pub struct DeviceSummary {
+    pub id: String,
+    pub name: String,
+    pub platform: Platform,
+    pub connection: Connection,
+}
+
+/// Lists every connected device that we can detect. Detection failures are
+/// logged and skipped, since this is meant to feed a device picker rather than
+/// to diagnose anything.
+pub fn summarize(env: &Env) -> Vec<DeviceSummary> {
+    let mut summaries = Vec::new();
 
     #[cfg(target_os = "macos")]
-    let section = {
-        use crate::apple::ios_deploy;
-        match ios_deploy::device_list(env) {
-            Ok(list) => section.with_victories(list),
-            Err(err) => section.with_failure(format!("Failed to get iOS device list: {}", err)),
+    match crate::apple::ios_deploy::device_list(env) {
+        // We pass `--no-wifi` to `ios-deploy`, so these are all USB.
+        Ok(list) => summaries.extend(list.iter().map(|device| DeviceSummary {
+            id: device.id().to_owned(),
+            name: device.name().to_owned(),
+            platform: Platform::Ios,
+            connection: Connection::Usb,
+        })),
+        Err(err) => log::warn!("failed to get iOS device list: {}", err),
+    }
+
+    match android::env::Env::from_env(env.clone())
+        .map_err(|err| err.to_string())
+        .and_then(|android_env| adb::device_list(&android_env).map_err(|err| err.to_string()))
+    {
+        Ok(list) => summaries.extend(list.iter().map(|device| DeviceSummary {
+            id: device.serial_no().to_owned(),
+            name: device.name().to_owned(),
+            platform: Platform::Android,
+            connection: Connection::from_android_serial_no(device.serial_no()),
+        })),
+        Err(err) => log::warn!("failed to get Android device list: {}", err),
+    }
+
+    summaries
+}
+
+#[cfg(target_os = "macos")]
+fn check_ios_version(
+    device: &crate::apple::device::Device<'_>,
+    min: crate::util::VersionDouble,
+) -> Item {
+    use crate::util::{VersionDouble, VersionTriple};
+    match device
+        .os_version()
+        .map(|raw| (raw, VersionTriple::from_str(raw)))
+    {
+        Some((raw, Ok(version))) if VersionDouble::new(version.major, version.minor) < min => {
+            Item::localized(
+                Label::Warning,
+                message!(
+                    "device-list.ios.version.low",
+                    device = device,
+                    version = raw,
+                    min = min,
+                ),
+            )
         }
+        Some((raw, Err(err))) => Item::localized(
+            Label::Warning,
+            message!(
+                "device-list.ios.version.invalid",
+                device = device,
+                version = format!("{:?}", raw),
+                error = err,
+            ),
+        ),
+        _ => Item::localized(
+            Label::Victory,
+            message!("device-list.device", device = device),
+        ),
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod devicectl {
+    use super::command_with_timeout;
+    use serde::Deserialize;
+    use std::{collections::HashMap, fs, time::Duration};
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ConnectionProperties {
+        #[serde(default)]
+        pairing_state: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct HardwareProperties {
+        #[serde(default)]
+        udid: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Device {
+        connection_properties: ConnectionProperties,
+        hardware_properties: HardwareProperties,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct DeviceList {
+        devices: Vec<Device>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Output {
+        result: DeviceList,
+    }
+
+    /// Maps each device's UDID (which is what `ios-deploy` calls its ID) to
+    /// whether it's been paired with this computer, i.e. whether somebody
+    /// tapped "Trust" on it.
+    pub fn paired_states(timeout: Duration) -> Result<HashMap<String, bool>, String> {
+        // `devicectl` only writes JSON to a file.
+        let dir = crate::util::temp_dir();
+        fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+        let path = dir.join("devicectl-devices.json");
+        command_with_timeout(
+            &format!(
+                "xcrun devicectl list devices --json-output {}",
+                path.display()
+            ),
+            timeout,
+        )
+        .map_err(|err| err.to_string())?;
+        let json = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+        let output = serde_json::from_str::<Output>(&json).map_err(|err| err.to_string())?;
+        Ok(output
+            .result
+            .devices
+            .into_iter()
+            .filter_map(|device| {
+                let udid = device.hardware_properties.udid?;
+                let paired =
+                    device.connection_properties.pairing_state.as_deref() == Some("paired");
+                Some((udid, paired))
+            })
+            .collect())
+    }
+}
+
+// This is `ios_deploy::device_list`, but with a timeout.
+#[cfg(target_os = "macos")]
+fn ios_device_list<'a>(
+    timeout: Duration,
+) -> Result<std::collections::BTreeSet<crate::apple::device::Device<'a>>, Error> {
+    let command = "ios-deploy --detect --timeout 1 --json --no-wifi";
+    let stdout = match command_with_timeout(command, timeout) {
+        // `ios-deploy` exits with an error when it doesn't find any devices.
+        Err(Error::NonZeroExit { .. }) => String::new(),
+        result => result?,
     };
+    crate::apple::ios_deploy::parse_detected_devices(&stdout).map_err(|_| {
+        Error::OutputParseFailed {
+            command: command.to_owned(),
+            output: stdout,
+        }
+    })
+}
+
+// Untrusted devices still show up in `ios-deploy`'s list, but deploying to
+// them fails.
+#[cfg(target_os = "macos")]
+fn check_ios_device(
+    device: &crate::apple::device::Device<'_>,
+    paired: &std::collections::HashMap<String, bool>,
+    config: Option<&Config>,
+) -> Item {
+    match (paired.get(device.id()), config) {
+        (Some(false), _) => Item::localized(
+            Label::Warning,
+            message!("device-list.ios.untrusted", device = device),
+        )
+        .with_fix("Unlock the device and tap \"Trust\" when asked whether to trust this computer"),
+        (_, Some(config)) => check_ios_version(device, config.apple().ios_version()),
+        (_, None) => Item::localized(
+            Label::Victory,
+            message!("device-list.device", device = device),
+        ),
+    }
+}
+
+// After its header, `adb devices` prints a line per device, with the serial
+// number and the device's state separated by a tab.
+fn adb_device_states(output: &str) -> Vec<(&str, &str)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split('\t');
+            match (columns.next(), columns.next()) {
+                (Some(serial_no), Some(state)) => Some((serial_no.trim(), state.trim())),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+// Devices in the `device` state are usable, and get checked in more detail
+// once we've listed them.
+fn check_adb_state(serial_no: &str, state: &str) -> Option<Item> {
+    match state {
+        "device" => None,
+        "unauthorized" => Some(
+            Item::localized(
+                Label::Warning,
+                message!("device-list.android.unauthorized", serial_no = serial_no),
+            )
+            .with_fix("Unlock the device and accept the USB debugging prompt; if there isn't one, turn USB debugging off and on again in Developer options"),
+        ),
+        "offline" => Some(
+            Item::localized(
+                Label::Warning,
+                message!("device-list.android.offline", serial_no = serial_no),
+            )
+            .with_fix("Reconnect the device, or run `adb kill-server` and try again"),
+        ),
+        // On Linux, this is followed by a link explaining udev rules.
+        _ if state.starts_with("no permissions") => Some(
+            Item::localized(
+                Label::Warning,
+                message!("device-list.android.no-permissions", serial_no = serial_no),
+            )
+            .with_fix("Add a udev rule for the device, then reconnect it"),
+        ),
+        _ => Some(Item::localized(
+            Label::Warning,
+            message!(
+                "device-list.android.unusable",
+                serial_no = serial_no,
+                state = format!("{:?}", state),
+            ),
+        )),
+    }
+}
+
+// Each emulator takes a console port and the adb port right after it, and
+// shows up in `adb devices` as `emulator-<console port>`. A single emulator
+// can't conflict with anything, so it isn't worth mentioning.
+fn check_emulator_ports(serial_nos: &[&str]) -> Vec<Item> {
+    let mut ports = serial_nos
+        .iter()
+        .filter_map(|serial_no| serial_no.strip_prefix("emulator-")?.parse::<u16>().ok())
+        .collect::<Vec<_>>();
+    if ports.len() < 2 {
+        return Vec::new();
+    }
+    ports.sort_unstable();
+    let mut items = ports
+        .iter()
+        .map(|port| {
+            Item::localized(
+                Label::Info,
+                message!(
+                    "device-list.android.emulator-ports",
+                    port = port,
+                    adb_port = u32::from(*port) + 1,
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+    items.extend(ports.windows(2).filter_map(|pair| {
+        (pair[1] - pair[0] < 2).then(|| {
+            Item::localized(
+                Label::Warning,
+                message!(
+                    "device-list.android.emulator-ports.overlap",
+                    first = pair[0],
+                    second = pair[1],
+                ),
+            )
+            .with_fix("Restart one of them with `-port` set to an unused even port, i.e. `emulator -avd <name> -port 5556`")
+        })
+    }));
+    items
+}
+
+fn check_android_devices(config: Option<&Config>, timeout: Duration, section: Section) -> Section {
+    match command_with_retries("adb devices", ATTEMPTS, RETRY_DELAY, timeout) {
+        Err(err @ Error::CommandTimedOut { .. }) => section.with_item(Item::warning(err)),
+        // Otherwise this would just look like there aren't any devices.
+        Err(Error::SpawnFailed { source, .. }) if source.kind() == io::ErrorKind::NotFound => {
+            section.with_item(
+                Item::localized(Label::Error, message!("device-list.android.adb-missing"))
+                    .with_fix("Install the SDK's platform-tools and add `$ANDROID_SDK_ROOT/platform-tools` to your `PATH`"),
+            )
+        }
+        Err(err) => section.with_item(Item::localized(
+            Label::Error,
+            message!("device-list.android.failed", error = err),
+        )),
+        Ok(output) => {
+            let states = adb_device_states(&output);
+            if states.is_empty() {
+                return section.with_item(Item::localized(
+                    Label::Info,
+                    message!("device-list.android.none"),
+                ));
+            }
+            let serial_nos = states
+                .iter()
+                .map(|(serial_no, _)| *serial_no)
+                .collect::<Vec<_>>();
+            let section = section.with_items(check_emulator_ports(&serial_nos));
+            section.with_items(states.iter().map(|(serial_no, state)| {
+                check_adb_state(serial_no, state)
+                    .unwrap_or_else(|| check_usable_android_device(serial_no, config, timeout))
+            }))
+        }
+    }
+}
+
+fn check_usable_android_device(
+    serial_no: &str,
+    config: Option<&Config>,
+    timeout: Duration,
+) -> Item {
+    let device = describe_android_device(serial_no, timeout);
+    match config {
+        Some(config) => check_api_level(
+            &device,
+            serial_no,
+            config.android().min_sdk_version(),
+            timeout,
+        ),
+        None => Item::localized(
+            Label::Victory,
+            message!("device-list.device", device = device),
+        ),
+    }
+}
 
-    let section = if let Ok(android_env) = android::env::Env::from_env(env.clone()) {
-        match adb::device_list(&android_env) {
-            Ok(list) => section.with_victories(list),
-            Err(err) => section.with_failure(format!("Failed to get Android device list: {}", err)),
+pub fn check(env: &Env, config: Option<&Config>, opts: &CheckOpts) -> Section {
+    let section = Section::new("Connected devices");
+    if opts.no_device_checks.yes() {
+        return section.with_item(Item::localized(
+            Label::Info,
+            message!("device-list.skipped"),
+        ));
+    }
+
+    #[cfg(target_os = "macos")]
+    let section = section.with_check("device-list.ios", |section| {
+        match ios_device_list(opts.timeout) {
+            Err(err @ Error::CommandTimedOut { .. }) => section.with_item(Item::warning(err)),
+            Ok(list) if list.is_empty() => section.with_item(Item::localized(
+                Label::Info,
+                message!("device-list.ios.none"),
+            )),
+            Ok(list) => {
+                // `devicectl` only ships with Xcode 15 and later; without it,
+                // we just can't tell whether devices are trusted.
+                let paired = devicectl::paired_states(opts.timeout).unwrap_or_else(|err| {
+                    log::info!("failed to get pairing states from `devicectl`: {}", err);
+                    Default::default()
+                });
+                section.with_items(
+                    list.iter()
+                        .map(|device| check_ios_device(device, &paired, config)),
+                )
+            }
+            Err(err) => section.with_item(Item::localized(
+                Label::Error,
+                message!("device-list.ios.failed", error = err),
+            )),
         }
+    });
+
+    let section = if android::env::Env::from_env(env.clone()).is_ok() {
+        section.with_check("device-list.android", |section| {
+            check_android_devices(config, opts.timeout, section)
+        })
     } else {
         section
     };
 
     if section.is_empty() {
-        section.with_victory("No connected devices were found")
+        section.with_item(Item::localized(Label::Info, message!("device-list.none")))
     } else {
         section
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(output, states,
+        case("List of devices attached\n\
+            AB1234DEFG\tdevice\n\
+            CD5678HIJK\tunauthorized\n\
+            emulator-5554\toffline\n\
+            ", vec![("AB1234DEFG", "device"), ("CD5678HIJK", "unauthorized"), ("emulator-5554", "offline")]
+        ),
+        case("* daemon started successfully\n\
+            List of devices attached\n\
+            EF9012LMNO\tno permissions (user in plugdev group; are your udev rules wrong?)\n\
+            ", vec![("EF9012LMNO", "no permissions (user in plugdev group; are your udev rules wrong?)")]
+        ),
+        case("List of devices attached\n\n", vec![]),
+    )]
+    fn test_adb_device_states(output: &str, states: Vec<(&str, &str)>) {
+        assert_eq!(adb_device_states(output), states);
+    }
+
+    #[rstest(
+        state,
+        usable,
+        case("device", true),
+        case("unauthorized", false),
+        case("offline", false),
+        case("no permissions", false)
+    )]
+    fn test_check_adb_state(state: &str, usable: bool) {
+        assert_eq!(check_adb_state("AB1234DEFG", state).is_none(), usable);
+    }
+
+    #[rstest(serial_nos, items, warnings,
+        case(vec!["emulator-5554"], 0, 0),
+        case(vec!["emulator-5554", "AB1234DEFG", "emulator-5556"], 2, 0),
+        case(vec!["emulator-5555", "emulator-5554"], 3, 1),
+    )]
+    fn test_check_emulator_ports(serial_nos: Vec<&str>, items: usize, warnings: usize) {
+        let checked = check_emulator_ports(&serial_nos);
+        assert_eq!(checked.len(), items);
+        assert_eq!(
+            checked.iter().filter(|item| item.is_warning()).count(),
+            warnings
+        );
+    }
+}
@@ -0,0 +1,752 @@
+use std::collections::HashMap;
+
+// The English wording for every message with an ID. Placeholders look like
+// `{name}`, and are filled in from the message's params.
+static DEFAULTS: &[(&str, &str)] = &[
+    ("android.env-path", "`{name}` is set to {path}"),
+    (
+        "android.env-path.resolved",
+        "`{name}` is set to {path}, which resolves to {real}",
+    ),
+    ("android.env-path.unset", "`{name}` isn't set"),
+    (
+        "android.env-path.missing",
+        "`{name}` is set to {path}, which doesn't exist",
+    ),
+    (
+        "android.env-path.dangling",
+        "`{name}` is set to {path}, which is a dangling symlink to {target}",
+    ),
+    (
+        "android.env-path.unresolved",
+        "`{name}` is set to {path}, which couldn't be resolved: {error}",
+    ),
+    ("android.env.failed", "{error}"),
+    ("android.sdk-root-vars", "Using the SDK at {path}"),
+    (
+        "android.sdk-root-vars.mismatch",
+        "`ANDROID_SDK_ROOT` is set to {sdk_root}, but `ANDROID_HOME` is set to {android_home}",
+    ),
+    ("android.sdk", "SDK v{version} installed at {path}"),
+    ("android.sdk.failed", "Failed to get SDK version: {error}"),
+    ("android.ndk", "NDK v{version} installed at {path}"),
+    ("android.ndk.failed", "Failed to get NDK version: {error}"),
+    (
+        "android.ndk-range",
+        "NDK v{version} is within the project's supported range",
+    ),
+    (
+        "android.ndk-range.too-old",
+        "NDK v{version} is older than v{min}, the oldest the project supports",
+    ),
+    (
+        "android.ndk-range.too-new",
+        "NDK v{version} is newer than v{max}, the newest the project supports",
+    ),
+    ("android.ndk-host", "NDK host toolchain: {host}"),
+    (
+        "android.ndk-host.missing",
+        "The NDK at {path} doesn't have a toolchain for this host ({host}); it only has {present}",
+    ),
+    (
+        "android.ndk-host.none",
+        "The NDK at {path} doesn't have a toolchain for this host ({host}); it doesn't have any prebuilt toolchains at all",
+    ),
+    ("android.linkers", "{triple} links with clang v{version}"),
+    (
+        "android.linkers.broken",
+        "The linker for {triple} is present, but doesn't run: {error}",
+    ),
+    ("android.linkers.failed", "{triple} can't be linked: {error}"),
+    ("android.installed-ndks", "NDK v{version} found at {path}"),
+    (
+        "android.installed-ndks.selected",
+        "NDK v{version} found at {path} (selected by `NDK_HOME`)",
+    ),
+    (
+        "android.installed-ndks.invalid",
+        "Failed to get version of NDK at {path}: {error}",
+    ),
+    (
+        "android.installed-ndks.unselected",
+        "{count} NDKs are installed side-by-side, but `NDK_HOME` doesn't point to any of them, so Gradle may use a different NDK than cargo-mobile does",
+    ),
+    (
+        "android.installed-ndks.failed",
+        "Failed to list installed NDKs: {error}",
+    ),
+    ("android.build-tools", "Build-tools v{version} installed"),
+    (
+        "android.build-tools.pinned-none",
+        "The project requires build-tools v{version}, but no versions are installed",
+    ),
+    (
+        "android.build-tools.pinned-missing",
+        "The project requires build-tools v{version}, but the only versions installed are {installed}",
+    ),
+    ("android.build-tools.none", "No build-tools are installed"),
+    ("android.compile-sdk", "Platform `android-{level}` installed"),
+    (
+        "android.compile-sdk.none",
+        "The project compiles against `android-{level}`, but no platforms are installed",
+    ),
+    (
+        "android.compile-sdk.missing",
+        "The project compiles against `android-{level}`, but the only platforms installed are {installed}",
+    ),
+    ("android.sdk-licenses", "SDK licenses accepted"),
+    (
+        "android.sdk-licenses.missing",
+        "SDK licenses haven't been accepted (checked {path}), so Gradle won't be able to download missing packages",
+    ),
+    (
+        "android.free-space",
+        "{size} GB free on the drive with the SDK",
+    ),
+    (
+        "android.free-space.low",
+        "Only {size} GB is free on the drive with the SDK, so builds and emulators may fail",
+    ),
+    (
+        "android.free-space.failed",
+        "Failed to check free disk space: {error}",
+    ),
+    ("android.emulator-accel", "Emulator acceleration: {details}"),
+    (
+        "android.emulator-accel.unavailable",
+        "The emulator can't use hardware acceleration, so it'll be unusably slow: {details}",
+    ),
+    (
+        "android.emulator-accel.no-emulator",
+        "The emulator isn't installed, so hardware acceleration wasn't checked",
+    ),
+    (
+        "android.emulator-accel.no-kvm",
+        "`/dev/kvm` doesn't exist, so the emulator can't use hardware acceleration",
+    ),
+    (
+        "android.emulator-accel.kvm-inaccessible",
+        "`/dev/kvm` isn't accessible, so the emulator can't use hardware acceleration: {error}",
+    ),
+    (
+        "android.emulator-accel.failed",
+        "Failed to check emulator acceleration: {error}",
+    ),
+    ("android.avds", "AVD: {name}"),
+    (
+        "android.avds.none",
+        "No AVDs exist, so there's no emulator to run on",
+    ),
+    (
+        "android.avds.no-emulator",
+        "The Android Emulator isn't installed, so you won't be able to run on an emulator",
+    ),
+    ("android.avds.failed", "Failed to list AVDs: {error}"),
+    (
+        "android.rosetta",
+        "{abis} system images are installed, but they can't run on Apple Silicon without Rosetta",
+    ),
+    ("android.java", "Java {version}"),
+    (
+        "android.java.old",
+        "Java {version} is on your `PATH`, but Android Gradle Plugin needs Java {min} or later",
+    ),
+    (
+        "android.java.home-mismatch",
+        "Java {version} is on your `PATH`, but `JAVA_HOME` is set to Java {home_version} at {path}",
+    ),
+    ("android.java.failed", "Failed to check Java version: {error}"),
+    ("android.application-id", "Application ID: {id}"),
+    (
+        "android.application-id.one-segment",
+        "The application ID {id} is invalid: it needs at least two segments, i.e. `com.example`",
+    ),
+    (
+        "android.application-id.empty-segment",
+        "The application ID {id} is invalid: it has an empty segment",
+    ),
+    (
+        "android.application-id.bad-start",
+        "The application ID {id} is invalid: segment {segment} doesn't start with a letter",
+    ),
+    (
+        "android.application-id.bad-char",
+        "The application ID {id} is invalid: segment {segment} contains {char}, but only ASCII letters, numbers, and underscores are allowed",
+    ),
+    (
+        "android.application-id.keyword",
+        "The application ID {id} is invalid: segment {segment} is a reserved keyword in Java/Kotlin",
+    ),
+    ("android.release-keystore", "Release key `{alias}` in {path}"),
+    (
+        "android.release-keystore.missing",
+        "The release keystore doesn't exist at {path}",
+    ),
+    (
+        "android.release-keystore.no-password",
+        "`{var}` isn't set, so the release keystore can't be unlocked",
+    ),
+    (
+        "android.release-keystore.unreadable",
+        "`keytool` couldn't read key `{alias}` from {path}: {error}",
+    ),
+    ("android.gradlew", "Gradle wrapper present at {path}"),
+    (
+        "android.gradlew.missing",
+        "Gradle wrapper is missing; checked at {path}",
+    ),
+    (
+        "android.gradlew.not-executable",
+        "Gradle wrapper at {path} isn't executable",
+    ),
+    (
+        "android.gradlew.system-gradle",
+        "System Gradle v{version} is installed",
+    ),
+    (
+        "android.gradle-agp",
+        "Gradle v{gradle} is compatible with Android Gradle Plugin v{agp}",
+    ),
+    (
+        "android.gradle-agp.too-old",
+        "Android Gradle Plugin v{agp} needs Gradle v{min} or later, but the wrapper uses Gradle v{gradle}",
+    ),
+    (
+        "android.gradle-agp.unknown",
+        "Android Gradle Plugin v{agp} is older than any we know about (with Gradle v{gradle})",
+    ),
+    (
+        "android.gradle-agp.no-agp",
+        "Couldn't find the Android Gradle Plugin version in the project's build scripts",
+    ),
+    (
+        "android.gradle-agp.no-gradle",
+        "Couldn't find the Gradle version in `gradle/wrapper/gradle-wrapper.properties`",
+    ),
+    ("apple.xcode", "Xcode v{version}"),
+    ("apple.xcode.failed", "Failed to check Xcode version: {error}"),
+    ("apple.developer-dir", "Active developer dir: {path}"),
+    (
+        "apple.developer-dir.failed",
+        "Failed to get active Xcode developer dir: {error}",
+    ),
+    ("apple.full-xcode", "Full Xcode install is active"),
+    (
+        "apple.full-xcode.clt-only",
+        "Only the Xcode command-line tools are active, so simulators and archiving won't work",
+    ),
+    (
+        "apple.full-xcode.failed",
+        "Failed to check for a full Xcode install: {error}",
+    ),
+    (
+        "apple.beta-xcode",
+        "The active Xcode at {path} is a beta (build {build}), so some build behavior may differ",
+    ),
+    (
+        "apple.beta-xcode.by-path",
+        "The active Xcode at {path} looks like a beta, so some build behavior may differ",
+    ),
+    (
+        "apple.beta-xcode.stable",
+        "The active Xcode is a stable release (build {build})",
+    ),
+    (
+        "apple.beta-xcode.not-beta",
+        "The active Xcode at {path} isn't a beta",
+    ),
+    (
+        "apple.deploy-backend",
+        "Deploying to devices with ios-deploy v{version}",
+    ),
+    (
+        "apple.deploy-backend.devicectl-only",
+        "`devicectl` is available for checking on devices, but `cargo apple run` deploys with ios-deploy",
+    ),
+    (
+        "apple.deploy-backend.none",
+        "Neither ios-deploy nor `devicectl` is available, so you won't be able to run on a device",
+    ),
+    (
+        "apple.macos-version",
+        "Xcode v{xcode} requires macOS {min} or later, but this is macOS {version}",
+    ),
+    (
+        "apple.macos-version.failed",
+        "Failed to check macOS version against Xcode's requirements: {error}",
+    ),
+    ("apple.xcode-plugin.plugin", "xcode-rust-plugin plugin present"),
+    (
+        "apple.xcode-plugin.plugin.absent",
+        "xcode-rust-plugin plugin absent",
+    ),
+    (
+        "apple.xcode-plugin.lang-spec",
+        "xcode-rust-plugin lang spec present",
+    ),
+    (
+        "apple.xcode-plugin.lang-spec.absent",
+        "xcode-rust-plugin lang spec absent",
+    ),
+    (
+        "apple.xcode-plugin.lang-metadata",
+        "xcode-rust-plugin lang metadata present",
+    ),
+    (
+        "apple.xcode-plugin.lang-metadata.absent",
+        "xcode-rust-plugin lang metadata absent",
+    ),
+    ("apple.xcode-plugin.fresh", "xcode-rust-plugin is up-to-date"),
+    ("apple.xcode-plugin.outdated", "xcode-rust-plugin is outdated"),
+    (
+        "apple.xcode-plugin.status-failed",
+        "Failed to check xcode-rust-plugin installation status: {error}",
+    ),
+    (
+        "apple.xcode-plugin.uuid",
+        "xcode-rust-plugin supports Xcode UUID {uuid}",
+    ),
+    (
+        "apple.xcode-plugin.uuid.unsupported",
+        "xcode-rust-plugin doesn't support Xcode UUID {uuid}",
+    ),
+    (
+        "apple.xcode-plugin.uuid-failed",
+        "Failed to check xcode-rust-plugin UUID status: {error}",
+    ),
+    (
+        "apple.xcode-plugin.context-failed",
+        "Failed to get xcode-rust-plugin context: {error}",
+    ),
+    ("apple.simulator-runtimes", "Simulator runtime: {name}"),
+    (
+        "apple.simulator-runtimes.none",
+        "No iOS simulator runtimes are available, so you won't be able to run on a simulator",
+    ),
+    (
+        "apple.simulator-runtimes.invalid",
+        "Failed to parse simulator runtimes: {error}",
+    ),
+    (
+        "apple.simulator-runtimes.failed",
+        "Failed to list simulator runtimes: {error}",
+    ),
+    (
+        "apple.signing-identities.one",
+        "1 valid code-signing identity found",
+    ),
+    (
+        "apple.signing-identities",
+        "{count} valid code-signing identities found",
+    ),
+    (
+        "apple.signing-identities.identity",
+        "Code-signing identity: {name}",
+    ),
+    (
+        "apple.signing-identities.unusable",
+        "Only self-signed or expired code-signing identities were found, so you won't be able to run on a device",
+    ),
+    (
+        "apple.signing-identities.none",
+        "No code-signing identities were found, so you won't be able to run on a device",
+    ),
+    (
+        "apple.signing-identities.failed",
+        "Failed to check code-signing identities: {error}",
+    ),
+    ("apple.simulator", "Simulator {name} ({udid})"),
+    (
+        "apple.simulator.unavailable",
+        "The configured simulator {name} exists, but its runtime is no longer installed",
+    ),
+    (
+        "apple.simulator.missing",
+        "The configured simulator {name} doesn't exist",
+    ),
+    ("apple.simulator.failed", "Failed to list simulators: {error}"),
+    (
+        "apple.simulator.invalid",
+        "Failed to parse simulators: {error}",
+    ),
+    ("apple.bundle-id", "Bundle ID: {id}"),
+    (
+        "apple.bundle-id.bad-char",
+        "The bundle ID {id} is invalid: it contains {char}, but only ASCII letters, numbers, hyphens, and periods are allowed",
+    ),
+    (
+        "apple.bundle-id.edge-period",
+        "The bundle ID {id} is invalid: it starts or ends with a period",
+    ),
+    (
+        "apple.bundle-id.empty-segment",
+        "The bundle ID {id} is invalid: it has an empty segment",
+    ),
+    (
+        "apple.ios-sdk",
+        "iOS SDK v{sdk} supports the deployment target of iOS {target}",
+    ),
+    (
+        "apple.ios-sdk.too-old",
+        "The iOS SDK is v{sdk}, but the project's deployment target is iOS {target}",
+    ),
+    (
+        "apple.ios-sdk.missing",
+        "Xcode doesn't have an iOS SDK installed",
+    ),
+    ("apple.ios-sdk.failed", "Failed to list Xcode SDKs: {error}"),
+    ("apple.ios-targets", "iOS targets installed: {targets}"),
+    (
+        "apple.ios-targets.invalid-arch",
+        "{arch} in `valid-archs` doesn't correspond to any iOS target",
+    ),
+    (
+        "apple.ios-targets.missing",
+        "iOS {kind} builds for `{arch}` need `{triple}`, which isn't installed",
+    ),
+    (
+        "apple.ios-targets.failed",
+        "Couldn't map iOS archs to Rust targets: {error}",
+    ),
+    ("apple.cocoapods", "CocoaPods v{version}"),
+    (
+        "apple.cocoapods.old",
+        "CocoaPods v{version} is installed, but `pod install` needs v{min} or later",
+    ),
+    (
+        "apple.cocoapods.invalid",
+        "Failed to parse CocoaPods version {version}: {error}",
+    ),
+    (
+        "apple.cocoapods.missing",
+        "This project uses pods, but CocoaPods isn't installed",
+    ),
+    (
+        "apple.cocoapods.failed",
+        "Failed to check CocoaPods version: {error}",
+    ),
+    ("apple.python", "{version} at {path}"),
+    (
+        "apple.python.not-3",
+        "`python3` at {path} is actually {version}",
+    ),
+    (
+        "apple.python.exited",
+        "`python3` at {path} doesn't run: it exited with {status}{stderr}",
+    ),
+    (
+        "apple.python.failed",
+        "`python3` at {path} doesn't run: {error}",
+    ),
+    (
+        "apple.python.missing",
+        "`python3` isn't on your `PATH`, so build scripts that use it will fail",
+    ),
+    ("apple.derived-data", "{path} is using {size} GB"),
+    (
+        "apple.derived-data.over-limit",
+        "{path} is using {size} GB, which is over the {limit} GB limit",
+    ),
+    (
+        "apple.derived-data.failed",
+        "Failed to check DerivedData size: {error}",
+    ),
+    ("apple.teams", "Development team: {name} ({id})"),
+    (
+        "apple.teams.failed",
+        "Failed to find development teams: {error}",
+    ),
+    ("cargo-mobile.version", "{version} ({commit})"),
+    (
+        "cargo-mobile.version.unknown-commit",
+        "{version} (unknown commit: {reason})",
+    ),
+    ("cargo-mobile.install-dir.found", "Installed at {path}"),
+    (
+        "cargo-mobile.install-dir.missing",
+        "The cargo-mobile installation directory is missing! Checked at {path}",
+    ),
+    ("cargo-mobile.os", "{name} v{version}"),
+    ("cargo-mobile.os.failed", "Failed to get OS info: {error}"),
+    ("cargo-mobile.rustc", "rustc v{version}"),
+    (
+        "cargo-mobile.rustc.link-broken",
+        "iOS linking is broken on rustc v{version}",
+    ),
+    ("cargo-mobile.locale.utf8", "Locale: `{name}={value}`"),
+    (
+        "cargo-mobile.locale.not-utf8",
+        "The locale is `{name}={value}`, which isn't UTF-8, so tools may print output that can't be read",
+    ),
+    (
+        "cargo-mobile.locale.unset",
+        "None of `LC_ALL`, `LC_CTYPE`, or `LANG` are set, so tools fall back to a non-UTF-8 locale and may print output that can't be read",
+    ),
+    ("cargo-mobile.config.found", "Project config found at {path}"),
+    (
+        "cargo-mobile.config.absent",
+        "Not in a cargo-mobile project, so project checks were skipped",
+    ),
+    ("cargo-mobile.targets", "Rust targets:"),
+    ("cargo-mobile.targets.installed", "{triple} installed"),
+    ("cargo-mobile.targets.missing", "{triple} isn't installed"),
+    (
+        "cargo-mobile.targets.failed",
+        "Failed to list installed Rust targets: {error}",
+    ),
+    (
+        "cargo-mobile.gen-freshness.stale",
+        "The project at {path} was generated from an older version of the config, so builds will use stale settings",
+    ),
+    (
+        "cargo-mobile.gen-freshness.fresh",
+        "The project at {path} is up-to-date with the config",
+    ),
+    (
+        "cargo-mobile.template-version.current",
+        "The project at {path} uses the current templates (v{version})",
+    ),
+    (
+        "cargo-mobile.template-version.old",
+        "The project at {path} was generated from v{version} of the templates, but this version of cargo-mobile expects v{expected}",
+    ),
+    (
+        "cargo-mobile.template-version.missing",
+        "The project at {path} doesn't record which templates it was generated from, so it likely predates v{expected}",
+    ),
+    ("cargo-mobile.required-env.set", "`{name}` is set to {value}"),
+    (
+        "cargo-mobile.required-env.set-by-config",
+        "`{name}` is set by the project config's `env` table",
+    ),
+    ("cargo-mobile.required-env.missing", "`{name}` isn't set"),
+    (
+        "cargo-mobile.required-env.not-unicode",
+        "`{name}` is set, but isn't valid unicode",
+    ),
+    (
+        "cargo-mobile.toolchain.matches",
+        "The active toolchain matches the pinned `{channel}` channel",
+    ),
+    (
+        "cargo-mobile.toolchain.mismatch",
+        "The project pins the `{channel}` channel, but the active toolchain is rustc v{version}",
+    ),
+    (
+        "cargo-mobile.toolchain.unknown",
+        "The project pins the `{channel}` toolchain, which couldn't be compared against the active one",
+    ),
+    ("cargo-mobile.path-tools.unique", "`{tool}` at {path}"),
+    (
+        "cargo-mobile.path-tools.shadowed",
+        "Found {count} copies of `{tool}` on PATH; {path} is used, shadowing {others}",
+    ),
+    ("cargo-mobile.shell", "Shell: {shell}"),
+    (
+        "cargo-mobile.shell.unknown",
+        "Shell: unknown (`SHELL` isn't set)",
+    ),
+    (
+        "cargo-mobile.mirrors.proxy",
+        "Checking package mirrors through the proxy at {proxy}",
+    ),
+    (
+        "cargo-mobile.mirrors.proxy-invalid",
+        "The proxy {proxy} is invalid, so package mirrors weren't checked: {error}",
+    ),
+    ("cargo-mobile.mirrors.reachable", "{name} is reachable"),
+    (
+        "cargo-mobile.mirrors.status",
+        "{name} ({url}) responded with HTTP {status}",
+    ),
+    (
+        "cargo-mobile.mirrors.unreachable",
+        "{name} ({url}) is unreachable: {error}",
+    ),
+    (
+        "cargo-mobile.packages.installed",
+        "System package `{package}` installed",
+    ),
+    (
+        "cargo-mobile.packages.missing",
+        "Required system packages aren't installed: {packages}",
+    ),
+    (
+        "cargo-mobile.packages.failed",
+        "Failed to check required system packages: {error}",
+    ),
+    ("device-list.device", "{device}"),
+    ("device-list.none", "No connected devices were found"),
+    ("device-list.android.none", "No Android devices are attached"),
+    (
+        "device-list.android.failed",
+        "Failed to get Android device list: {error}",
+    ),
+    (
+        "device-list.android.adb-missing",
+        "`adb` wasn't found on your `PATH`, so Android devices can't be detected",
+    ),
+    (
+        "device-list.android.unauthorized",
+        "{serial_no} hasn't authorized USB debugging from this computer",
+    ),
+    ("device-list.android.offline", "{serial_no} is offline"),
+    (
+        "device-list.android.no-permissions",
+        "{serial_no} can't be accessed, since you don't have permission to use it",
+    ),
+    (
+        "device-list.android.unusable",
+        "{serial_no} is in the {state} state, so it can't be used",
+    ),
+    (
+        "device-list.android.emulator-ports",
+        "Emulator emulator-{port} is using console port {port} and adb port {adb_port}",
+    ),
+    (
+        "device-list.android.emulator-ports.overlap",
+        "Emulators emulator-{first} and emulator-{second} have overlapping ports, so `adb` may talk to the wrong one",
+    ),
+    (
+        "device-list.android.api-level.low",
+        "{device} is running API level {level}, which is below the configured `min-sdk-version` of {min}",
+    ),
+    (
+        "device-list.android.api-level.failed",
+        "Failed to get API level of {device}: {error}",
+    ),
+    ("device-list.ios.none", "No iOS devices are attached"),
+    (
+        "device-list.ios.failed",
+        "Failed to get iOS device list: {error}",
+    ),
+    (
+        "device-list.ios.untrusted",
+        "{device} hasn't trusted this computer, so deploying to it will fail",
+    ),
+    (
+        "device-list.ios.version.low",
+        "{device} is running iOS {version}, which is below the configured `ios-version` of {min}, so the app won't install",
+    ),
+    (
+        "device-list.ios.version.invalid",
+        "Failed to parse iOS version {version} of {device}: {error}",
+    ),
+    (
+        "device-list.skipped",
+        "Skipped checking for connected devices (pass `--device-checks` to check them)",
+    ),
+    ("doctor.build-script", "Build script {path} is executable"),
+    ("doctor.build-script.missing", "Build script {path} is missing"),
+    (
+        "doctor.build-script.not-executable",
+        "Build script {path} isn't executable",
+    ),
+    (
+        "doctor.expected",
+        "{tool} v{version} matches the expected v{expected}",
+    ),
+    (
+        "doctor.expected.mismatch",
+        "{tool} v{version} is installed, but the team expects v{expected}",
+    ),
+    ("doctor.skipped", "Skipped `{id}` by config"),
+    (
+        "doctor.slow-skipped",
+        "Skipped checking {check} (pass `--include-slow` to check it)",
+    ),
+];
+
+/// Looks up the English wording for a message ID.
+pub fn default_template(id: &str) -> Option<&'static str> {
+    DEFAULTS
+        .iter()
+        .find(|(default_id, _)| *default_id == id)
+        .map(|(_, template)| *template)
+}
+
+/// Item text with a stable ID, so that its wording can be overridden (i.e.
+/// translated) independently of the check that produces it.
+#[derive(Clone, Debug)]
+pub struct Message {
+    id: &'static str,
+    params: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    pub fn new(id: &'static str) -> Self {
+        debug_assert!(
+            default_template(id).is_some(),
+            "developer error: message {:?} has no default wording",
+            id
+        );
+        Self {
+            id,
+            params: Default::default(),
+        }
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.params.push((name, value.to_string()));
+        self
+    }
+
+    // Placeholders without a matching param are left as-is, which makes a typo
+    // in an override easy to spot.
+    fn render_with(&self, template: &str) -> String {
+        self.params
+            .iter()
+            .fold(template.to_owned(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            })
+    }
+
+    /// Renders the message, preferring the wording in `overrides` if there's
+    /// one for this ID.
+    pub fn render(&self, overrides: &HashMap<String, String>) -> String {
+        match overrides.get(self.id) {
+            Some(template) => self.render_with(template),
+            None => self.render_with(default_template(self.id).unwrap_or(self.id)),
+        }
+    }
+}
+
+macro_rules! message {
+    ($id:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::doctor::section::message::Message::new($id)
+            $(.with(stringify!($name), $value))*
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(overrides, expected,
+        case(&[], "x86_64-apple-ios installed"),
+        case(&[("cargo-mobile.targets.installed", "{triple} ist installiert")], "x86_64-apple-ios ist installiert"),
+    )]
+    fn test_render(overrides: &[(&str, &str)], expected: &str) {
+        let overrides = overrides
+            .iter()
+            .map(|(id, template)| (id.to_string(), template.to_string()))
+            .collect();
+        let message = message!(
+            "cargo-mobile.targets.installed",
+            triple = "x86_64-apple-ios"
+        );
+        assert_eq!(message.render(&overrides), expected);
+    }
+
+    #[test]
+    fn test_defaults_are_unique() {
+        for (index, (id, _)) in DEFAULTS.iter().enumerate() {
+            assert!(
+                DEFAULTS[index + 1..].iter().all(|(other, _)| other != id),
+                "message {:?} has more than one default",
+                id
+            );
+        }
+    }
+}
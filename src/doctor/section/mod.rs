@@ -1,65 +1,596 @@
+#[macro_use]
+mod message;
+
 pub mod android;
 #[cfg(target_os = "macos")]
 pub mod apple;
 pub mod cargo_mobile;
 pub mod device_list;
+pub mod since;
 
-use crate::util::{
-    self,
-    cli::{colors, TextWrapper},
+use self::message::Message;
+use crate::{
+    opts::{
+        Ascii, IncludeSlow, NoColor, NoiseLevel, OmitSkipped, Plain, ProblemsOnly, SortSeverity,
+    },
+    util::{
+        self,
+        cli::{colors, TextWrapper},
+    },
 };
 use colored::Colorize as _;
-use std::fmt::Debug;
+use serde::{ser::SerializeStruct as _, Deserialize, Serialize, Serializer};
+use std::{
+    cell::RefCell,
+    cmp::Reverse,
+    collections::HashMap,
+    fmt::{self, Debug, Display},
+    io::{self, Read as _, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
 
-#[derive(Clone, Copy, Debug)]
-enum Label {
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to spawn `{command}`: {source}")]
+    SpawnFailed { command: String, source: io::Error },
+    #[error("Failed to wait for `{command}`: {source}")]
+    WaitFailed { command: String, source: io::Error },
+    #[error("Failed to read output of `{command}`: {source}")]
+    ReadFailed { command: String, source: io::Error },
+    #[error("`{command}` failed: {source}{}", stderr_suffix(.stderr))]
+    CommandFailed {
+        command: String,
+        stderr: String,
+        source: bossy::Error,
+    },
+    #[error("Couldn't make sense of the output of `{command}`: {output:?}")]
+    OutputParseFailed { command: String, output: String },
+    #[error("`{command}` failed with {status}{}", stderr_suffix(.stderr))]
+    NonZeroExit {
+        command: String,
+        status: ExitStatus,
+        stderr: String,
+    },
+    #[error("`{command}` didn't finish within {dur:?}, so it was killed")]
+    CommandTimedOut { command: String, dur: Duration },
+    #[error("{source} (gave up after {attempts} attempts)")]
+    RetriesExhausted { attempts: u32, source: Box<Error> },
+}
+
+// Checkouts on some filesystems lose the exec bit, which makes builds fail in
+// strange ways. Windows doesn't have one to lose.
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt as _;
+    path.metadata()
+        .map(|metadata| metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or_default()
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+// `restore` says how to get a missing script back, which depends on what runs
+// it.
+fn check_script(path: &Path, restore: &str) -> Item {
+    let pretty = util::contract_home(path).unwrap_or_else(|_| path.display().to_string());
+    let quoted = format!("{:?}", pretty);
+    if !path.is_file() {
+        Item::localized(
+            Label::Error,
+            message!("doctor.build-script.missing", path = quoted),
+        )
+        .with_fix(restore)
+    } else if !is_executable(path) {
+        Item::localized(
+            Label::Error,
+            message!("doctor.build-script.not-executable", path = quoted),
+        )
+        .with_fix(format!("Run `chmod +x {}`", pretty))
+    } else {
+        Item::localized(
+            Label::Victory,
+            message!("doctor.build-script", path = quoted),
+        )
+    }
+}
+
+// Expected versions only need to match as far as they go.
+fn version_matches(expected: &str, detected: &str) -> bool {
+    let detected = detected.split('.').collect::<Vec<_>>();
+    expected
+        .split('.')
+        .enumerate()
+        .all(|(index, part)| detected.get(index) == Some(&part))
+}
+
+// Compares against the team's `doctor.expected` version for `tool`, if there
+// is one.
+fn check_expected(tool: &str, expected: Option<&str>, detected: &str) -> Option<Item> {
+    let expected = expected?;
+    Some(if version_matches(expected, detected) {
+        Item::localized(
+            Label::Victory,
+            message!(
+                "doctor.expected",
+                tool = tool,
+                version = detected,
+                expected = expected,
+            ),
+        )
+    } else {
+        Item::localized(
+            Label::Warning,
+            message!(
+                "doctor.expected.mismatch",
+                tool = tool,
+                version = detected,
+                expected = expected,
+            ),
+        )
+        .with_fix(format!("Switch to {} v{}", tool, expected))
+    })
+}
+
+// Tools tend to explain themselves on stderr, and that explanation is usually
+// the most useful part of the report.
+fn stderr_suffix(stderr: &str) -> String {
+    if stderr.is_empty() {
+        String::new()
+    } else {
+        format!("; stderr: {}", stderr)
+    }
+}
+
+thread_local! {
+    // When we're being noisy, every command a check runs (and its raw output)
+    // is noted here, so that failures can be reproduced by hand. Each section
+    // is checked on its own thread, so this never mixes sections up.
+    static ECHOED: RefCell<Option<Vec<Item>>> = RefCell::new(None);
+}
+
+fn echo(command: &str, result: Result<&str, &Error>) {
+    ECHOED.with(|echoed| {
+        if let Some(echoed) = echoed.borrow_mut().as_mut() {
+            echoed.push(Item::info(format!("Ran `{}`", command)));
+            echoed.push(Item::info(match result {
+                Ok(output) => format!("Output: {:?}", output),
+                Err(err) => format!("Failed: {}", err),
+            }));
+        }
+    })
+}
+
+fn take_echoed() -> Vec<Item> {
+    ECHOED.with(|echoed| {
+        echoed
+            .borrow_mut()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    })
+}
+
+/// Runs `f`, echoing the commands it runs unless `noise_level` is polite.
+/// Echoes come right before the next item added to a section, and any left
+/// over at the end are returned.
+pub(crate) fn echoing<T>(noise_level: NoiseLevel, f: impl FnOnce() -> T) -> (T, Vec<Item>) {
+    ECHOED.with(|echoed| *echoed.borrow_mut() = (!noise_level.polite()).then(Vec::new));
+    let result = f();
+    let leftover = take_echoed();
+    ECHOED.with(|echoed| *echoed.borrow_mut() = None);
+    (result, leftover)
+}
+
+// Windows can only run batch files (i.e. `sdkmanager.bat` and `gradle.bat`)
+// through `cmd`, and running them directly fails like the tool is missing.
+// Everything else runs directly, since going through `cmd` would hide a
+// missing tool behind `cmd`'s exit code, and a timeout would only kill `cmd`.
+#[cfg(windows)]
+fn shell_words(command: &str) -> String {
+    const BATCH_TOOLS: &[&str] = &["avdmanager", "gradle", "gradlew", "sdkmanager"];
+    let program = command.split_whitespace().next().unwrap_or_default();
+    let program = program.to_ascii_lowercase();
+    if program.ends_with(".bat")
+        || program.ends_with(".cmd")
+        || BATCH_TOOLS.contains(&program.as_str())
+    {
+        format!("cmd /C {}", command)
+    } else {
+        command.to_owned()
+    }
+}
+
+#[cfg(not(windows))]
+fn shell_words(command: &str) -> String {
+    command.to_owned()
+}
+
+// Everything a command printed, and how it exited.
+#[derive(Debug)]
+struct Output {
+    stdout: String,
+    stderr: String,
+    status: ExitStatus,
+}
+
+impl Output {
+    // Plenty of tools print their version to stderr (i.e. `java -version`, or
+    // `python --version` before Python 3.4), so this is what version checks
+    // should look at.
+    fn stdout_or_stderr(&self) -> &str {
+        if self.stdout.trim().is_empty() {
+            &self.stderr
+        } else {
+            &self.stdout
+        }
+    }
+}
+
+// `command` is for display, and is what ends up in any errors.
+fn output_of(command: &str, process: &mut Command) -> Result<Output, Error> {
+    let result = process
+        .stdin(Stdio::null())
+        .output()
+        .map(|output| Output {
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+            status: output.status,
+        })
+        .map_err(|source| Error::SpawnFailed {
+            command: command.to_owned(),
+            source,
+        });
+    echo(command, result.as_ref().map(Output::stdout_or_stderr));
+    result
+}
+
+// Like `impure_parse`, the command string is split on whitespace and the
+// command inherits our environment. A non-zero exit is left to the caller.
+fn command_output(command: &str) -> Result<Output, Error> {
+    let words = shell_words(command);
+    let mut args = words.split_whitespace();
+    let program = args
+        .next()
+        .expect("developer error: `command_output` was given an empty command");
+    output_of(command, Command::new(program).args(args))
+}
+
+// The common case of `command_output`, where only a successful command's stdout
+// matters.
+fn command(command: &str) -> Result<String, Error> {
+    let output = command_output(command)?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(Error::NonZeroExit {
+            command: command.to_owned(),
+            status: output.status,
+            stderr: output.stderr,
+        })
+    }
+}
+
+// `bossy` doesn't give us a way to stop waiting on a command, so this talks to
+// `std::process` directly. Like `impure_parse`, the command string is split on
+// whitespace and the command inherits our environment.
+fn command_with_timeout(command: &str, dur: Duration) -> Result<String, Error> {
+    let result = run_with_timeout(command, dur);
+    echo(command, result.as_ref().map(String::as_str));
+    result
+}
+
+fn run_with_timeout(command: &str, dur: Duration) -> Result<String, Error> {
+    let words = shell_words(command);
+    let mut args = words.split_whitespace();
+    let program = args
+        .next()
+        .expect("developer error: `command_with_timeout` was given an empty command");
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|source| Error::SpawnFailed {
+            command: command.to_owned(),
+            source,
+        })?;
+    // Reading on another thread keeps a chatty command from blocking on a full
+    // pipe while we're polling it.
+    let mut stdout = child
+        .stdout
+        .take()
+        .expect("developer error: `command_with_timeout` stdout not captured");
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+    let deadline = Instant::now() + dur;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
+            Ok(None) => {
+                // The command may have exited since we last checked, in which
+                // case there's nothing left to kill.
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::CommandTimedOut {
+                    command: command.to_owned(),
+                    dur,
+                });
+            }
+            Err(source) => {
+                return Err(Error::WaitFailed {
+                    command: command.to_owned(),
+                    source,
+                })
+            }
+        }
+    };
+    let output = reader
+        .join()
+        .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+        .map_err(|source| Error::ReadFailed {
+            command: command.to_owned(),
+            source,
+        })?;
+    if status.success() {
+        Ok(output)
+    } else {
+        // stderr isn't captured, since nothing reads it while we're polling.
+        Err(Error::NonZeroExit {
+            command: command.to_owned(),
+            status,
+            stderr: String::new(),
+        })
+    }
+}
+
+// Some commands (i.e. `adb devices` right after plugging in a device) fail
+// transiently, so this retries on a non-zero exit. Anything else is returned
+// right away, since trying again wouldn't help.
+fn command_with_retries(
+    command: &str,
+    attempts: u32,
+    delay: Duration,
+    timeout: Duration,
+) -> Result<String, Error> {
+    let mut attempt = 1;
+    loop {
+        match command_with_timeout(command, timeout) {
+            Err(err @ Error::NonZeroExit { .. }) if attempt < attempts => {
+                log::info!("attempt {} of {} failed: {}", attempt, attempts, err);
+                thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(err @ Error::NonZeroExit { .. }) if attempts > 1 => {
+                return Err(Error::RetriesExhausted {
+                    attempts,
+                    source: Box::new(err),
+                })
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Item severity, ordered from least to most severe.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Label {
     Victory,
+    Info,
     Warning,
     Error,
 }
 
-impl Label {
-    fn title_symbol(self) -> &'static str {
+/// The glyphs used to mark sections and items.
+#[derive(Clone, Copy, Debug)]
+pub enum Symbols {
+    Unicode,
+    /// For consoles that render the unicode symbols as boxes, i.e. a lot of
+    /// Windows consoles and CI logs.
+    Ascii,
+}
+
+impl Symbols {
+    /// Uses ASCII if `ascii` is set or if the locale doesn't look like UTF-8.
+    pub fn detect(ascii: Ascii) -> Self {
+        if ascii.yes() || !Self::locale_is_utf8() {
+            Self::Ascii
+        } else {
+            Self::Unicode
+        }
+    }
+
+    fn locale_is_utf8() -> bool {
+        // These take precedence in this order, and the first non-empty one
+        // wins.
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(std::env::var_os)
+            .find(|value| !value.is_empty());
+        match locale {
+            Some(locale) => {
+                let locale = locale.to_string_lossy().to_ascii_lowercase();
+                locale.contains("utf-8") || locale.contains("utf8")
+            }
+            // Windows doesn't use locale variables, but Windows Terminal can
+            // render unicode just fine.
+            None if cfg!(windows) => std::env::var_os("WT_SESSION").is_some(),
+            // With no locale set, we get the ASCII-only "C" locale.
+            None => false,
+        }
+    }
+
+    fn arrow(self) -> &'static str {
         match self {
-            Self::Victory | Self::Warning => "✔",
-            Self::Error => "!",
+            Self::Unicode => "→",
+            Self::Ascii => "->",
+        }
+    }
+}
+
+impl Label {
+    /// The more severe of the two.
+    pub fn max(self, other: Self) -> Self {
+        Ord::max(self, other)
+    }
+
+    /// The most severe of `labels`, ignoring `Info`, which is neutral. With
+    /// nothing else to go on, this is `Victory`.
+    pub fn worst(labels: impl IntoIterator<Item = Self>) -> Self {
+        labels
+            .into_iter()
+            .filter(|label| *label != Self::Info)
+            .fold(Self::Victory, Self::max)
+    }
+
+    fn title_symbol(self, symbols: Symbols) -> &'static str {
+        match (symbols, self) {
+            (Symbols::Unicode, Self::Victory | Self::Info | Self::Warning) => "✔",
+            (Symbols::Unicode, Self::Error) => "!",
+            (Symbols::Ascii, Self::Victory | Self::Info | Self::Warning) => "OK",
+            (Symbols::Ascii, Self::Error) => "!",
         }
     }
 
-    fn item_symbol(self) -> &'static str {
+    fn item_symbol(self, symbols: Symbols) -> &'static str {
+        match (symbols, self) {
+            (Symbols::Unicode, Self::Victory) => "•",
+            (Symbols::Unicode, Self::Info) => "ℹ",
+            (Symbols::Unicode, Self::Warning | Self::Error) => "✗",
+            (Symbols::Ascii, Self::Victory) => "-",
+            (Symbols::Ascii, Self::Info) => "i",
+            (Symbols::Ascii, Self::Warning | Self::Error) => "x",
+        }
+    }
+
+    // Unlike the symbols, these are stable and distinct for every label, so
+    // they're safe to grep for.
+    fn token(self) -> &'static str {
         match self {
-            Self::Victory => "•",
-            Self::Warning | Self::Error => "✗",
+            Self::Victory => "[PASS]",
+            Self::Info => "[INFO]",
+            Self::Warning => "[WARN]",
+            Self::Error => "[FAIL]",
         }
     }
 
     fn color(self) -> colored::Color {
         match self {
             Self::Victory => colors::VICTORY,
+            Self::Info => colors::INFO,
             Self::Warning => colors::WARNING,
             Self::Error => colors::ERROR,
         }
     }
 
-    fn format_title(self, title: &str) -> colored::ColoredString {
-        format!("[{}] {}", self.title_symbol(), title)
+    fn format_title(self, title: &str, symbols: Symbols) -> colored::ColoredString {
+        format!("[{}] {}", self.title_symbol(symbols), title)
             .color(self.color())
             .bold()
     }
 
-    fn format_item(self, msg: &str) -> colored::ColoredString {
-        let item = format!("{} {}", self.item_symbol(), msg);
+    fn format_item(self, msg: &str, symbols: Symbols) -> colored::ColoredString {
+        let item = format!("{} {}", self.item_symbol(symbols), msg);
         match self {
             Self::Victory => item.normal(),
+            Self::Info => item.color(self.color()),
             _ => item.color(self.color()).bold(),
         }
     }
 }
 
-#[derive(Debug)]
-struct Item {
+/// Options controlling how sections are printed as text.
+#[derive(Clone, Copy, Debug)]
+pub struct PrintOpts {
+    pub no_color: NoColor,
+    /// Prefix each item with a token like `[FAIL]`, for log scraping.
+    pub plain: Plain,
+    /// Only print warnings and failures, skipping sections that have neither.
+    pub problems_only: ProblemsOnly,
+    /// Print each section's worst items first, instead of in the order they
+    /// were checked.
+    pub sort_severity: SortSeverity,
+    pub symbols: Symbols,
+    /// When not polite, items note how long their checks took.
+    pub noise_level: NoiseLevel,
+}
+
+// The symbols are part of the text itself, so severity is still readable when
+// the color is stripped.
+fn paint(s: colored::ColoredString, no_color: NoColor) -> String {
+    if no_color.yes() {
+        s.clear().to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// A fix that the doctor knows how to apply on its own.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FixAction {
+    AddRustTarget(String),
+    /// Runs the given `sdkmanager` interactively, since every license has to be
+    /// accepted by hand.
+    AcceptAndroidLicenses(PathBuf),
+}
+
+impl Display for FixAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AddRustTarget(triple) => write!(f, "Install the Rust target {}", triple),
+            Self::AcceptAndroidLicenses(_) => write!(f, "Accept the Android SDK licenses"),
+        }
+    }
+}
+
+impl FixAction {
+    pub fn run(&self) -> bossy::Result<()> {
+        match self {
+            Self::AddRustTarget(triple) => util::rustup_add(triple).map(|_status| ()),
+            Self::AcceptAndroidLicenses(sdkmanager) => bossy::Command::impure(sdkmanager)
+                .with_arg("--licenses")
+                .run_and_wait()
+                .map(|_status| ()),
+        }
+    }
+}
+
+/// A single result within a section, i.e. a tool's version or a problem with
+/// it.
+#[derive(Debug, Serialize)]
+pub struct Item {
+    /// Identifies the check that produced this item, i.e. `android.java`, so
+    /// that it can be skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<&'static str>,
+    #[serde(rename = "severity")]
     label: Label,
+    #[serde(rename = "message")]
     msg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remediation: Option<String>,
+    #[serde(skip)]
+    action: Option<FixAction>,
+    // When present, `msg` was rendered from this, and can be rendered again
+    // with different wording.
+    #[serde(skip)]
+    message: Option<Message>,
+    // How long the check that produced this took, if it was measured.
+    #[serde(skip)]
+    duration: Option<Duration>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Item>,
 }
 
 impl<T: ToString, E: ToString> From<Result<T, E>> for Item {
@@ -71,27 +602,113 @@ impl<T: ToString, E: ToString> From<Result<T, E>> for Item {
 impl Item {
     fn new(label: Label, msg: impl ToString) -> Self {
         Self {
+            id: None,
             label,
             msg: msg.to_string(),
+            remediation: None,
+            action: None,
+            message: None,
+            duration: None,
+            children: Default::default(),
+        }
+    }
+
+    fn localized(label: Label, message: Message) -> Self {
+        Self {
+            message: Some(message.clone()),
+            ..Self::new(label, message.render(&Default::default()))
+        }
+    }
+
+    // Groups are only one level deep, since that's all `Section::print`
+    // renders. The group's label is the worst of its children's.
+    fn group(message: Message, children: Vec<Item>) -> Self {
+        let label = Label::worst(children.iter().map(|child| child.label));
+        Self {
+            children,
+            ..Self::localized(label, message)
         }
     }
 
-    fn victory(msg: impl ToString) -> Self {
+    // Times `check`, for when the item's check doesn't go through
+    // `Section::with_check`.
+    fn timed(check: impl FnOnce() -> Self) -> Self {
+        let start = Instant::now();
+        let item = check();
+        Self {
+            duration: Some(start.elapsed()),
+            ..item
+        }
+    }
+
+    pub fn victory(msg: impl ToString) -> Self {
         Self::new(Label::Victory, msg)
     }
 
-    fn warning(msg: impl ToString) -> Self {
+    fn info(msg: impl ToString) -> Self {
+        Self::new(Label::Info, msg)
+    }
+
+    pub fn warning(msg: impl ToString) -> Self {
         Self::new(Label::Warning, msg)
     }
 
-    fn failure(msg: impl ToString) -> Self {
+    pub fn failure(msg: impl ToString) -> Self {
         Self::new(Label::Error, msg)
     }
 
+    fn with_fix(self, fix: impl ToString) -> Self {
+        Self {
+            remediation: Some(fix.to_string()),
+            ..self
+        }
+    }
+
+    fn with_id(self, id: &'static str) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    fn with_action(self, action: FixAction) -> Self {
+        Self {
+            action: Some(action),
+            ..self
+        }
+    }
+
     fn from_result(result: Result<impl ToString, impl ToString>) -> Self {
         util::unwrap_either(result.map(Self::victory).map_err(Self::failure))
     }
 
+    fn localize(mut self, overrides: &HashMap<String, String>) -> Self {
+        if let Some(message) = &self.message {
+            self.msg = message.render(overrides);
+        }
+        self.children = self
+            .children
+            .into_iter()
+            .map(|child| child.localize(overrides))
+            .collect();
+        self
+    }
+
+    fn actions(&self) -> impl Iterator<Item = &FixAction> {
+        self.action.iter().chain(
+            self.children
+                .iter()
+                .filter_map(|child| child.action.as_ref()),
+        )
+    }
+
+    // A group counts as its children rather than as an item of its own.
+    fn leaves(&self) -> impl Iterator<Item = &Item> {
+        std::iter::once(self)
+            .filter(|item| item.children.is_empty())
+            .chain(&self.children)
+    }
+
     fn is_warning(&self) -> bool {
         matches!(self.label, Label::Warning)
     }
@@ -100,8 +717,99 @@ impl Item {
         matches!(self.label, Label::Error)
     }
 
-    fn format(&self) -> colored::ColoredString {
-        self.label.format_item(&self.msg)
+    fn push_annotations(&self, title: &str, lines: &mut Vec<String>) {
+        // https://docs.github.com/en/actions/using-workflow-commands-for-github-actions#example-creating-an-annotation-for-an-error-message
+        fn escape_data(s: &str) -> String {
+            s.replace('%', "%25")
+                .replace('\r', "%0D")
+                .replace('\n', "%0A")
+        }
+        fn escape_property(s: &str) -> String {
+            escape_data(s).replace(':', "%3A").replace(',', "%2C")
+        }
+
+        let command = match self.label {
+            Label::Warning => "warning",
+            Label::Error => "error",
+            Label::Victory | Label::Info => return,
+        };
+        let msg = match &self.remediation {
+            Some(remediation) => format!("{}\n{}", self.msg, remediation),
+            None => self.msg.clone(),
+        };
+        lines.push(format!(
+            "::{} title={}::{}",
+            command,
+            escape_property(title),
+            escape_data(&msg)
+        ));
+        for child in &self.children {
+            child.push_annotations(title, lines);
+        }
+    }
+
+    fn push_problem_lines(&self, indent: usize, lines: &mut Vec<String>) {
+        if self.label >= Label::Warning {
+            lines.push(format!(
+                "{:indent$}{} {}",
+                "",
+                self.label.token(),
+                self.msg,
+                indent = indent
+            ));
+            for child in &self.children {
+                child.push_problem_lines(indent + 2, lines);
+            }
+        }
+    }
+
+    fn format(&self, symbols: Symbols) -> colored::ColoredString {
+        self.label.format_item(&self.msg, symbols)
+    }
+
+    fn format_remediation(&self, symbols: Symbols) -> Option<colored::ColoredString> {
+        self.remediation
+            .as_ref()
+            .map(|fix| format!("{} {}", symbols.arrow(), fix).color(self.label.color()))
+    }
+
+    fn print(
+        &self,
+        out: &mut impl Write,
+        bullet_wrapper: &TextWrapper,
+        remediation_wrapper: &TextWrapper,
+        opts: PrintOpts,
+    ) -> io::Result<()> {
+        // Fast checks aren't worth drawing attention to.
+        static MIN_SHOWN_DURATION: Duration = Duration::from_millis(100);
+        let line = paint(self.format(opts.symbols), opts.no_color);
+        let line = match self.duration {
+            Some(duration) if !opts.noise_level.polite() && duration >= MIN_SHOWN_DURATION => {
+                format!(
+                    "{} {}",
+                    line,
+                    paint(
+                        format!("({:.1}s)", duration.as_secs_f64()).dimmed(),
+                        opts.no_color
+                    )
+                )
+            }
+            _ => line,
+        };
+        let line = if opts.plain.yes() {
+            format!("{} {}", self.label.token(), line)
+        } else {
+            line
+        };
+        writeln!(out, "{}", bullet_wrapper.fill(&line))?;
+        if let Some(remediation) = self.format_remediation(opts.symbols) {
+            writeln!(
+                out,
+                "{}",
+                remediation_wrapper.fill(&paint(remediation, opts.no_color))
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -109,79 +817,498 @@ impl Item {
 pub struct Section {
     title: String,
     items: Vec<Item>,
+    // How long the whole section took to check, if it was measured.
+    duration: Option<Duration>,
+}
+
+// Items are the same if they'd print the same, so two checks that notice the
+// same problem count as duplicates of each other.
+impl PartialEq for Item {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label && self.msg == other.msg
+    }
+}
+
+impl Serialize for Section {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Section", 3)?;
+        state.serialize_field("title", &self.title)?;
+        state.serialize_field("label", &self.label())?;
+        state.serialize_field("items", &self.items)?;
+        state.end()
+    }
 }
 
 impl Section {
-    fn new(title: impl ToString) -> Self {
+    pub fn new(title: impl ToString) -> Self {
         Self {
             title: title.to_string(),
             items: Default::default(),
+            duration: None,
         }
     }
 
-    fn with_item(mut self, item: impl Into<Item>) -> Self {
-        self.items.push(item.into());
+    pub fn new_with_items(
+        title: impl ToString,
+        items: impl IntoIterator<Item = impl Into<Item>>,
+    ) -> Self {
+        Self::new(title).with_items(items)
+    }
+
+    pub fn with_item(mut self, item: impl Into<Item>) -> Self {
+        let item = item.into();
+        self.items.extend(take_echoed());
+        self.items.push(item);
+        self
+    }
+
+    /// Adds a victory for `Ok` and a failure for `Err`, which covers most checks
+    /// that just run a command and report on it, i.e.
+    /// `section.add_result(command("adb --version"))`.
+    pub fn add_result(&mut self, result: Result<impl ToString, impl Into<Error>>) -> &mut Self {
+        self.items.extend(take_echoed());
+        self.items
+            .push(Item::from_result(result.map_err(Into::<Error>::into)));
+        self
+    }
+
+    /// The consuming version of [`Section::add_result`].
+    pub fn with_result(mut self, result: Result<impl ToString, impl Into<Error>>) -> Self {
+        self.add_result(result);
+        self
+    }
+
+    pub fn with_items(mut self, items: impl IntoIterator<Item = impl Into<Item>>) -> Self {
+        let items = items.into_iter().map(Into::into).collect::<Vec<_>>();
+        self.items.extend(take_echoed());
+        self.items.extend(items);
         self
     }
 
-    fn with_victory(self, victory: impl ToString) -> Self {
-        self.with_item(Item::victory(victory))
+    // Skipped slow checks still get an item, so that nobody mistakes them for
+    // passing.
+    fn with_slow(
+        self,
+        include_slow: IncludeSlow,
+        description: &str,
+        f: impl FnOnce(Self) -> Self,
+    ) -> Self {
+        if include_slow.yes() {
+            f(self)
+        } else {
+            self.with_item(Item::localized(
+                Label::Info,
+                message!("doctor.slow-skipped", check = description),
+            ))
+        }
+    }
+
+    // The check's duration goes on its first item, so that it's only shown
+    // once.
+    fn stamp_since(mut self, start: usize, id: &'static str, began: Instant) -> Self {
+        // Commands that didn't lead to an item of their own.
+        self.items.extend(take_echoed());
+        if let Some(item) = self.items.get_mut(start) {
+            item.duration.get_or_insert_with(|| began.elapsed());
+        }
+        for item in &mut self.items[start..] {
+            item.id.get_or_insert(id);
+        }
+        self
+    }
+
+    // Every item `f` adds that doesn't already have an ID gets `id`, which is
+    // handy for checks that add a variable number of items.
+    fn with_check(self, id: &'static str, f: impl FnOnce(Self) -> Self) -> Self {
+        let start = self.items.len();
+        let began = Instant::now();
+        f(self).stamp_since(start, id, began)
+    }
+
+    fn try_with_check<E>(
+        self,
+        id: &'static str,
+        f: impl FnOnce(Self) -> Result<Self, E>,
+    ) -> Result<Self, E> {
+        let start = self.items.len();
+        let began = Instant::now();
+        f(self).map(|section| section.stamp_since(start, id, began))
+    }
+
+    /// Removes the items from the checks in `skip`. Unless `omit` is set, each
+    /// skipped check leaves behind a note saying so.
+    pub fn skip(self, skip: &[String], omit: OmitSkipped) -> Self {
+        let mut skipped = Vec::new();
+        let mut items = Vec::with_capacity(self.items.len());
+        for item in self.items {
+            match item.id {
+                Some(id) if skip.iter().any(|skip| skip == id) => {
+                    if omit.no() && !skipped.contains(&id) {
+                        items.push(
+                            Item::localized(Label::Info, message!("doctor.skipped", id = id))
+                                .with_id(id),
+                        );
+                    }
+                    skipped.push(id);
+                }
+                _ => items.push(item),
+            }
+        }
+        Self { items, ..self }
+    }
+
+    /// Rewords items using `overrides`, which maps message IDs to templates.
+    pub fn localize(self, overrides: &HashMap<String, String>) -> Self {
+        let items = self
+            .items
+            .into_iter()
+            .map(|item| item.localize(overrides))
+            .collect();
+        Self { items, ..self }
+    }
+
+    /// Just the first failure, if there is one.
+    pub fn into_first_error(self) -> Option<Self> {
+        let index = self.items.iter().position(Item::is_failure)?;
+        let mut items = self.items;
+        Some(Self {
+            items: vec![items.swap_remove(index)],
+            ..self
+        })
+    }
+
+    /// Stably sorts the items from worst to best, so that problems come first.
+    pub fn sort_items_by_severity(&mut self) {
+        self.items.sort_by_key(|item| Reverse(item.label));
+    }
+
+    /// Removes repeated items, keeping the first of each.
+    pub fn dedup(&mut self) {
+        let mut seen = Vec::<Item>::with_capacity(self.items.len());
+        for item in self.items.drain(..) {
+            if !seen.contains(&item) {
+                seen.push(item);
+            }
+        }
+        self.items = seen;
     }
 
-    fn with_failure(self, failure: impl ToString) -> Self {
-        self.with_item(Item::failure(failure))
+    /// Appends `other`'s items to this section. `other`'s title is dropped.
+    pub fn merge(&mut self, other: Section) {
+        self.items.extend(other.items);
     }
 
-    fn with_items(mut self, items: impl IntoIterator<Item = impl Into<Item>>) -> Self {
-        self.items.extend(items.into_iter().map(Into::into));
+    /// The consuming version of [`Section::merge`].
+    pub fn with_merged(mut self, other: Section) -> Self {
+        self.merge(other);
         self
     }
 
-    fn with_victories(self, victories: impl IntoIterator<Item = impl ToString>) -> Self {
-        self.with_items(victories.into_iter().map(Item::victory))
+    /// The fixes for every item that has one, in order.
+    pub fn fix_actions(&self) -> impl Iterator<Item = &FixAction> {
+        self.items.iter().flat_map(Item::actions)
     }
 
     pub fn is_empty(&self) -> bool {
         self.items.is_empty()
     }
 
-    fn has_error(&self) -> bool {
+    pub fn has_error(&self) -> bool {
         self.items.iter().any(Item::is_failure)
     }
 
-    fn has_warning(&self) -> bool {
+    pub fn has_warning(&self) -> bool {
         self.items.iter().any(Item::is_warning)
     }
 
-    fn label(&self) -> Label {
-        if self.has_error() {
-            Label::Error
-        } else if self.has_warning() {
-            Label::Warning
-        } else {
-            Label::Victory
+    /// Returns the number of victories, warnings, and failures (in that
+    /// order). `Info` items aren't counted, since they aren't checks.
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.items.iter().flat_map(Item::leaves).fold(
+            (0, 0, 0),
+            |(victories, warnings, failures), item| match item.label {
+                Label::Victory => (victories + 1, warnings, failures),
+                Label::Info => (victories, warnings, failures),
+                Label::Warning => (victories, warnings + 1, failures),
+                Label::Error => (victories, warnings, failures + 1),
+            },
+        )
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    pub(crate) fn with_duration(self, duration: Duration) -> Self {
+        Self {
+            duration: Some(duration),
+            ..self
         }
     }
 
-    pub fn print(&self, wrapper: &TextWrapper) {
+    /// The message of the first item from the check `id`, if that check ran.
+    pub fn message_of(&self, id: &str) -> Option<&str> {
+        self.items
+            .iter()
+            .find(|item| item.id == Some(id))
+            .map(|item| item.msg.as_str())
+    }
+
+    // Every warning and failure as a GitHub Actions workflow command, so that
+    // they show up as annotations on the run.
+    pub(crate) fn github_annotations(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for item in &self.items {
+            item.push_annotations(&self.title, &mut lines);
+        }
+        lines
+    }
+
+    // Every warning and failure as plain text, under the section title. Empty
+    // if there aren't any.
+    pub(crate) fn problem_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for item in &self.items {
+            item.push_problem_lines(2, &mut lines);
+        }
+        if !lines.is_empty() {
+            lines.insert(0, format!("{}:", self.title));
+        }
+        lines
+    }
+
+    /// The label of the section's worst item. `Info` items are neutral, so
+    /// they never affect this.
+    pub fn worst_label(&self) -> Label {
+        Label::worst(self.items.iter().map(|item| item.label))
+    }
+
+    /// The section's overall health, which is its [`Section::worst_label`].
+    pub fn label(&self) -> Label {
+        self.worst_label()
+    }
+
+    pub fn print(
+        &self,
+        out: &mut impl Write,
+        wrapper: &TextWrapper,
+        opts: PrintOpts,
+    ) -> io::Result<()> {
+        let shown =
+            |item: &&Item| opts.problems_only.no() || item.is_failure() || item.is_warning();
+        let mut items = self.items.iter().filter(shown).collect::<Vec<_>>();
+        if opts.sort_severity.yes() {
+            items.sort_by_key(|item| Reverse(item.label));
+        }
+        if items.is_empty() && opts.problems_only.yes() {
+            return Ok(());
+        }
         static BULLET_INDENT: &str = "    ";
         static HANGING_INDENT: &str = "      ";
+        static REMEDIATION_INDENT: &str = "        ";
+        static CHILD_BULLET_INDENT: &str = "        ";
+        static CHILD_HANGING_INDENT: &str = "          ";
+        static CHILD_REMEDIATION_INDENT: &str = "            ";
         let bullet_wrapper = wrapper
             .clone()
             .initial_indent(BULLET_INDENT)
             .subsequent_indent(HANGING_INDENT);
-        println!(
+        let remediation_wrapper = wrapper
+            .clone()
+            .initial_indent(HANGING_INDENT)
+            .subsequent_indent(REMEDIATION_INDENT);
+        let child_bullet_wrapper = wrapper
+            .clone()
+            .initial_indent(CHILD_BULLET_INDENT)
+            .subsequent_indent(CHILD_HANGING_INDENT);
+        let child_remediation_wrapper = wrapper
+            .clone()
+            .initial_indent(CHILD_HANGING_INDENT)
+            .subsequent_indent(CHILD_REMEDIATION_INDENT);
+        writeln!(
+            out,
             "\n{}",
-            // The `.to_string()` at the end is necessary for the color/bold to
-            // actually show - otherwise, the colored string just `AsRef`s to
-            // satisfy `TextWrapper::fill` and the formatting is left behind.
-            wrapper.fill(&self.label().format_title(&self.title).to_string())
-        );
-        for report_bullet in &self.items {
-            println!(
-                "{}",
-                bullet_wrapper.fill(&report_bullet.format().to_string())
-            );
+            // Going through `paint` (and thus `.to_string()`) is necessary for
+            // the color/bold to actually show - otherwise, the colored string
+            // just `AsRef`s to satisfy `TextWrapper::fill` and the formatting is
+            // left behind.
+            wrapper.fill(&paint(
+                self.label().format_title(&self.title, opts.symbols),
+                opts.no_color
+            ))
+        )?;
+        for report_bullet in items {
+            report_bullet.print(out, &bullet_wrapper, &remediation_wrapper, opts)?;
+            for child in report_bullet.children.iter().filter(shown) {
+                child.print(out, &child_bullet_wrapper, &child_remediation_wrapper, opts)?;
+            }
         }
+        Ok(())
+    }
+}
+
+pub fn print_summary(
+    out: &mut impl Write,
+    sections: &[Section],
+    worst: Label,
+    elapsed: Option<Duration>,
+    wrapper: &TextWrapper,
+    opts: PrintOpts,
+) -> io::Result<()> {
+    fn plural(count: usize, noun: &str) -> String {
+        format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+    }
+
+    let (victories, warnings, failures) = sections
+        .iter()
+        .map(Section::counts)
+        .fold((0, 0, 0), |(victories, warnings, failures), (v, w, f)| {
+            (victories + v, warnings + w, failures + f)
+        });
+    let mut summary = format!(
+        "{} passed, {}, {}",
+        victories,
+        plural(warnings, "warning"),
+        plural(failures, "error"),
+    );
+    if let Some(elapsed) = elapsed {
+        summary.push_str(&format!(" in {:.1}s", elapsed.as_secs_f64()));
+    }
+    writeln!(
+        out,
+        "\n{}",
+        wrapper.fill(&paint(summary.color(worst.color()).bold(), opts.no_color))
+    )?;
+    let breakdown = sections
+        .iter()
+        .filter_map(|section| {
+            section
+                .duration
+                .map(|duration| format!("{}: {:.1}s", section.title, duration.as_secs_f64()))
+        })
+        .collect::<Vec<_>>();
+    if !opts.noise_level.polite() && !breakdown.is_empty() {
+        writeln!(
+            out,
+            "{}",
+            wrapper.fill(&paint(breakdown.join(", ").dimmed(), opts.no_color))
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_label_order() {
+        assert!(Label::Error > Label::Warning);
+        assert!(Label::Warning > Label::Info);
+        assert!(Label::Info > Label::Victory);
+        assert_eq!(Label::Warning.max(Label::Error), Label::Error);
+        assert_eq!(
+            Label::worst(vec![Label::Info, Label::Victory]),
+            Label::Victory
+        );
+    }
+
+    #[rstest(
+        expected,
+        detected,
+        matches,
+        case("15", "15.2", true),
+        case("15.2", "15.2", true),
+        case("25.2", "25.2.9519653", true),
+        case("15.2", "15.20", false),
+        case("1.75.0", "1.75", false)
+    )]
+    fn test_version_matches(expected: &str, detected: &str, matches: bool) {
+        assert_eq!(version_matches(expected, detected), matches);
+    }
+
+    #[test]
+    fn test_github_annotations() {
+        let section = Section::new_with_items(
+            "Tools, etc.",
+            vec![
+                Item::victory("fine"),
+                Item::failure("100% broken").with_fix("Fix it"),
+            ],
+        );
+        assert_eq!(
+            section.github_annotations(),
+            vec!["::error title=Tools%2C etc.::100%25 broken%0AFix it".to_owned()]
+        );
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut section = Section::new_with_items(
+            "Repetitive",
+            vec![
+                Item::warning("same"),
+                Item::victory("same"),
+                Item::warning("same"),
+                Item::failure("different"),
+            ],
+        );
+        section.dedup();
+        assert_eq!(
+            section.items,
+            vec![
+                Item::warning("same"),
+                Item::victory("same"),
+                Item::failure("different"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mixed_section_label() {
+        let section = Section::new_with_items(
+            "Mixed",
+            vec![Item::victory("fine"), Item::warning("not great")],
+        );
+        assert!(matches!(section.label(), Label::Warning));
+        let section = section.with_item(Item::failure("broken"));
+        assert!(matches!(section.label(), Label::Error));
+        assert!(matches!(
+            Section::new("Empty")
+                .with_item(Item::victory("fine"))
+                .label(),
+            Label::Victory
+        ));
+    }
+
+    #[test]
+    fn test_add_result_failure() {
+        let mut section = Section::new("test");
+        section
+            .add_result(Ok::<_, Error>("fine"))
+            .add_result(Err::<String, _>(Error::CommandTimedOut {
+                command: "adb devices".to_owned(),
+                dur: Duration::from_secs(1),
+            }));
+        assert!(!section.items[0].is_failure());
+        assert!(section.items[1].is_failure());
+        assert_eq!(
+            section.items[1].msg,
+            "`adb devices` didn't finish within 1s, so it was killed"
+        );
+        assert!(Section::new("test")
+            .with_result(Err::<String, _>(Error::CommandTimedOut {
+                command: "adb devices".to_owned(),
+                dur: Duration::from_secs(1),
+            }))
+            .has_error());
     }
 }
@@ -0,0 +1,192 @@
+use super::{paint, Label, PrintOpts};
+use crate::util::cli::TextWrapper;
+use colored::Colorize as _;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error("Failed to read previous report {path:?}: {source}")]
+    ReadFailed { path: PathBuf, source: io::Error },
+    #[error("Previous report {path:?} isn't a JSON doctor report: {source}")]
+    ParseFailed {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+// Just the parts of a serialized report that we compare.
+#[derive(Debug, Deserialize)]
+struct SavedItem {
+    id: Option<String>,
+    severity: Label,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SavedSection {
+    items: Vec<SavedItem>,
+}
+
+/// The worst label of every check in a report, along with the message of the
+/// item that earned it. Items without an ID can't be matched up between
+/// reports, so they're left out.
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    checks: BTreeMap<String, (Label, String)>,
+}
+
+impl Snapshot {
+    fn from_sections(sections: Vec<SavedSection>) -> Self {
+        let mut checks = BTreeMap::<String, (Label, String)>::new();
+        for item in sections.into_iter().flat_map(|section| section.items) {
+            if let Some(id) = item.id {
+                match checks.get(&id) {
+                    Some((worst, _)) if *worst >= item.severity => (),
+                    _ => {
+                        checks.insert(id, (item.severity, item.message));
+                    }
+                }
+            }
+        }
+        Self { checks }
+    }
+
+    /// Takes a snapshot of anything that serializes like a report, which
+    /// keeps this in sync with the JSON format for free.
+    pub fn of(report: &impl Serialize) -> Result<Self, serde_json::Error> {
+        serde_json::to_value(report)
+            .and_then(serde_json::from_value)
+            .map(Self::from_sections)
+    }
+
+    /// Loads a report saved with `--save` (or `--format json`).
+    pub fn load(path: &Path) -> Result<Self, LoadError> {
+        let contents = fs::read_to_string(path).map_err(|source| LoadError::ReadFailed {
+            path: path.to_owned(),
+            source,
+        })?;
+        serde_json::from_str(&contents)
+            .map(Self::from_sections)
+            .map_err(|source| LoadError::ParseFailed {
+                path: path.to_owned(),
+                source,
+            })
+    }
+
+    fn is_problem(label: Label) -> bool {
+        label >= Label::Warning
+    }
+
+    // The checks that started or stopped being problems since `before`, each
+    // with the label to color it by and a description of the change.
+    fn changes<'a>(&'a self, before: &'a Self) -> Vec<(&'a str, Label, String)> {
+        before
+            .checks
+            .keys()
+            .chain(self.checks.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|id| {
+                let was = before.checks.get(id).map(|(label, _)| *label);
+                match (was, self.checks.get(id)) {
+                    (was, Some((label, message)))
+                        if Self::is_problem(*label) && was.map_or(true, |was| was < *label) =>
+                    {
+                        Some((
+                            id.as_str(),
+                            *label,
+                            format!("{} {}: {}", describe(was), label.token(), message),
+                        ))
+                    }
+                    (Some(was), now)
+                        if Self::is_problem(was) && now.map_or(true, |(label, _)| *label < was) =>
+                    {
+                        Some((
+                            id.as_str(),
+                            Label::Victory,
+                            format!(
+                                "{} {}",
+                                describe(Some(was)),
+                                now.map_or("(no longer checked)", |(label, _)| label.token())
+                            ),
+                        ))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Prints the checks that started or stopped being problems since
+    /// `before`.
+    pub fn print_changes(
+        &self,
+        before: &Self,
+        before_path: &Path,
+        out: &mut impl Write,
+        wrapper: &TextWrapper,
+        opts: PrintOpts,
+    ) -> io::Result<()> {
+        let changes = self.changes(before);
+        if changes.is_empty() {
+            return writeln!(out, "\nNo changes since {:?}", before_path);
+        }
+        writeln!(out, "\nChanges since {:?}:", before_path)?;
+        let bullet_wrapper = wrapper
+            .clone()
+            .initial_indent("    ")
+            .subsequent_indent("      ");
+        for (id, label, description) in changes {
+            let line = format!("{} {}", id, description).color(label.color());
+            writeln!(out, "{}", bullet_wrapper.fill(&paint(line, opts.no_color)))?;
+        }
+        Ok(())
+    }
+}
+
+fn describe(label: Option<Label>) -> String {
+    format!("{} ->", label.map_or("(new)", Label::token))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snapshot(json: &str) -> Snapshot {
+        Snapshot::from_sections(serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn test_changes() {
+        let before = snapshot(
+            r#"[{"items": [
+                {"id": "a", "severity": "victory", "message": "fine"},
+                {"id": "b", "severity": "error", "message": "broken"},
+                {"id": "c", "severity": "warning", "message": "iffy"}
+            ]}]"#,
+        );
+        let after = snapshot(
+            r#"[{"items": [
+                {"id": "a", "severity": "error", "message": "broken now"},
+                {"id": "b", "severity": "victory", "message": "fixed"},
+                {"id": "c", "severity": "warning", "message": "still iffy"},
+                {"id": "d", "severity": "warning", "message": "new"}
+            ]}]"#,
+        );
+        assert_eq!(
+            after.changes(&before),
+            vec![
+                ("a", Label::Error, "[PASS] -> [FAIL]: broken now".to_owned()),
+                ("b", Label::Victory, "[FAIL] -> [PASS]".to_owned()),
+                ("d", Label::Warning, "(new) -> [WARN]: new".to_owned()),
+            ]
+        );
+    }
+}
@@ -58,6 +58,14 @@ pub enum Error {
     AndroidEnvFailed(android::env::Error),
     AndroidInitFailed(android::project::Error),
     DotCargoWriteFailed(dot_cargo::WriteError),
+    GenHashWriteFailed {
+        path: PathBuf,
+        cause: io::Error,
+    },
+    TemplateVersionWriteFailed {
+        path: PathBuf,
+        cause: io::Error,
+    },
     DotFirstInitDeleteFailed {
         path: PathBuf,
         cause: io::Error,
@@ -83,12 +91,29 @@ impl Reportable for Error {
             #[cfg(target_os = "macos")]
             Self::AppleInitFailed(err) => err.report(),
             Self::DotCargoWriteFailed(err) => err.report(),
+            Self::GenHashWriteFailed { path, cause } => Report::error(format!("Failed to write config hash to {:?}", path), cause),
+            Self::TemplateVersionWriteFailed { path, cause } => Report::error(format!("Failed to write template version to {:?}", path), cause),
             Self::DotFirstInitDeleteFailed { path, cause } => Report::action_request(format!("Failed to delete first init dot file {:?}; the project generated successfully, but `cargo mobile init` will have unexpected results unless you manually delete this file!", path), cause),
             Self::OpenInEditorFailed(err) => Report::error("Failed to open project in editor (your project generated successfully though, so no worries!)", err),
         }
     }
 }
 
+// This lets `doctor` tell when the config or the templates have changed since
+// the project was generated.
+fn write_gen_metadata(config: &Config, project_dir: &Path) -> Result<(), Error> {
+    config
+        .write_gen_hash(project_dir)
+        .map_err(|cause| Error::GenHashWriteFailed {
+            path: Config::gen_hash_path(project_dir),
+            cause,
+        })?;
+    Config::write_template_version(project_dir).map_err(|cause| Error::TemplateVersionWriteFailed {
+        path: Config::template_version_path(project_dir),
+        cause,
+    })
+}
+
 pub fn exec(
     wrapper: &TextWrapper,
     non_interactive: opts::NonInteractive,
@@ -185,6 +210,7 @@ pub fn exec(
             &filter,
         )
         .map_err(Error::AppleInitFailed)?;
+        write_gen_metadata(&config, &config.apple().project_dir())?;
     } else {
         println!("Skipping iOS init, since it's marked as unsupported in your Cargo.toml metadata");
     }
@@ -192,16 +218,19 @@ pub fn exec(
     // Generate Android Studio project
     if metadata.android().supported() {
         match android::env::Env::new() {
-            Ok(env) => android::project::gen(
-                config.android(),
-                metadata.android(),
-                &env,
-                &bike,
-                wrapper,
-                &filter,
-                &mut dot_cargo,
-            )
-            .map_err(Error::AndroidInitFailed)?,
+            Ok(env) => {
+                android::project::gen(
+                    config.android(),
+                    metadata.android(),
+                    &env,
+                    &bike,
+                    wrapper,
+                    &filter,
+                    &mut dot_cargo,
+                )
+                .map_err(Error::AndroidInitFailed)?;
+                write_gen_metadata(&config, &config.android().project_dir())?;
+            }
             Err(err) => {
                 if err.sdk_or_ndk_issue() {
                     Report::action_request(
@@ -41,6 +41,28 @@ impl NoiseLevel {
 
 yes_or_no!(pub ForceColor);
 
+yes_or_no!(pub NoColor);
+
+yes_or_no!(pub Plain);
+
+yes_or_no!(pub Ascii);
+
+yes_or_no!(pub ProblemsOnly);
+
+yes_or_no!(pub SortSeverity);
+
+yes_or_no!(pub Fix);
+
+yes_or_no!(pub IncludeSlow);
+
+yes_or_no!(pub OmitSkipped);
+
+yes_or_no!(pub FirstError);
+
+yes_or_no!(pub NoDeviceChecks);
+
+yes_or_no!(pub Watch);
+
 yes_or_no!(pub SkipDevTools);
 
 yes_or_no!(pub ReinstallDeps);
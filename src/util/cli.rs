@@ -1,7 +1,7 @@
 use crate::{opts, util};
 use colored::Colorize as _;
 use once_cell_regex::exports::once_cell::sync::Lazy;
-use std::fmt::{Debug, Display};
+use std::fmt::{self, Debug, Display};
 use structopt::{
     clap::{self, AppSettings},
     StructOpt,
@@ -102,6 +102,7 @@ pub mod colors {
     pub const WARNING: Color = BrightYellow;
     pub const ACTION_REQUEST: Color = BrightMagenta;
     pub const VICTORY: Color = BrightGreen;
+    pub const INFO: Color = BrightCyan;
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -143,6 +144,12 @@ pub struct Report {
     details: String,
 }
 
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.msg, self.details)
+    }
+}
+
 impl Report {
     pub fn new(label: Label, msg: impl Display, details: impl Display) -> Self {
         Self {
@@ -235,6 +242,17 @@ fn init_logging(noise_level: opts::NoiseLevel) {
     Builder::from_env(env).init();
 }
 
+// `COLUMNS` wins over asking the terminal, since it's the usual way to
+// override the width (and it's often all that's available in CI). Otherwise,
+// `termwidth` falls back to 80 if there's no terminal to ask.
+fn term_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns| columns > 0)
+        .unwrap_or_else(textwrap::termwidth)
+}
+
 #[derive(Debug)]
 pub enum Exit {
     Report(Report),
@@ -258,7 +276,7 @@ impl Exit {
     }
 
     pub fn main(inner: impl FnOnce(&TextWrapper) -> Result<(), Self>) {
-        let wrapper = TextWrapper::with_splitter(textwrap::termwidth(), textwrap::NoHyphenation);
+        let wrapper = TextWrapper::with_splitter(term_width(), textwrap::NoHyphenation);
         if let Err(exit) = inner(&wrapper) {
             exit.do_the_thing(wrapper)
         }
@@ -433,28 +433,38 @@ impl RustVersion {
 
     pub fn valid(&self) -> bool {
         if cfg!(target_os = "macos") {
-            const LAST_GOOD_STABLE: VersionTriple = VersionTriple::new(1, 45, 2);
-            const NEXT_GOOD_STABLE: VersionTriple = VersionTriple::new(1, 49, 0);
-            const FIRST_GOOD_NIGHTLY: (u32, u32, u32) = (2020, 10, 24);
-
-            let old_good = self.triple <= LAST_GOOD_STABLE;
-            let new_good = self.triple >= NEXT_GOOD_STABLE
-                && self
-                    .details
-                    .as_ref()
-                    .map(|details| details.date >= FIRST_GOOD_NIGHTLY)
-                    .unwrap_or_else(|| {
-                        log::warn!("output of `rustc --version` didn't contain date info; continuing with the assumption that the release date is at least 2020-10-24");
-                        true
-                    });
-
-            old_good || new_good
+            !is_ios_link_broken(self)
         } else {
             true
         }
     }
 }
 
+/// The first stable release that broke linking for iOS.
+pub const LINK_BUG_FIRST_BAD: VersionTriple = VersionTriple::new(1, 46, 0);
+/// The first stable release that fixed linking for iOS again.
+pub const LINK_BUG_FIRST_FIXED: VersionTriple = VersionTriple::new(1, 49, 0);
+// Nightlies carried the `LINK_BUG_FIRST_FIXED` version number for a while
+// before the fix actually landed.
+const LINK_BUG_FIRST_FIXED_NIGHTLY: (u32, u32, u32) = (2020, 10, 24);
+
+pub fn is_ios_link_broken(version: &RustVersion) -> bool {
+    if version.triple < LINK_BUG_FIRST_BAD {
+        false
+    } else if version.triple < LINK_BUG_FIRST_FIXED {
+        true
+    } else {
+        version
+            .details
+            .as_ref()
+            .map(|details| details.date < LINK_BUG_FIRST_FIXED_NIGHTLY)
+            .unwrap_or_else(|| {
+                log::warn!("output of `rustc --version` didn't contain date info; continuing with the assumption that the release date is at least 2020-10-24");
+                false
+            })
+    }
+}
+
 pub fn prepend_to_path(path: impl Display, base_path: impl Display) -> String {
     format!("{}:{}", path, base_path)
 }
@@ -594,8 +604,8 @@ pub enum InstalledCommitMsgError {
     ReadFailed { path: PathBuf, source: io::Error },
 }
 
-pub fn installed_commit_msg() -> Result<Option<String>, InstalledCommitMsgError> {
-    let path = install_dir()?.join("commit");
+fn read_version_info(name: &str) -> Result<Option<String>, InstalledCommitMsgError> {
+    let path = install_dir()?.join(name);
     if path.is_file() {
         std::fs::read_to_string(&path)
             .map(Some)
@@ -605,10 +615,27 @@ pub fn installed_commit_msg() -> Result<Option<String>, InstalledCommitMsgError>
     }
 }
 
+pub fn installed_commit_msg() -> Result<Option<String>, InstalledCommitMsgError> {
+    read_version_info("commit")
+}
+
+/// The abbreviated hash of the commit this was built from.
+pub fn installed_commit_hash() -> Result<Option<String>, InstalledCommitMsgError> {
+    read_version_info("commit-hash").map(|hash| hash.map(|hash| hash.trim().to_owned()))
+}
+
 pub fn format_commit_msg(msg: String) -> String {
     format!("Contains commits up to {:?}", msg)
 }
 
+// FNV-1a, which unlike `DefaultHasher` is guaranteed not to change between Rust
+// releases, so it's safe to write to disk.
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 pub fn unwrap_either<T>(result: Result<T, T>) -> T {
     match result {
         Ok(t) | Err(t) => t,
@@ -685,3 +712,46 @@ impl<T: Debug> Serialize for OneOrMany<T> {
         serializer.serialize_str(&serialized_str)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(major, minor, patch, date, broken,
+        case(1, 45, 2, None, false),
+        case(1, 46, 0, None, true),
+        case(1, 48, 0, Some((2020, 11, 16)), true),
+        case(1, 49, 0, Some((2020, 10, 23)), true),
+        case(1, 49, 0, Some((2020, 10, 24)), false),
+        case(1, 49, 0, None, false),
+    )]
+    fn test_is_ios_link_broken(
+        major: u32,
+        minor: u32,
+        patch: u32,
+        date: Option<(u32, u32, u32)>,
+        broken: bool,
+    ) {
+        let version = RustVersion {
+            triple: VersionTriple::new(major, minor, patch),
+            flavor: None,
+            details: date.map(|date| RustVersionDetails {
+                hash: "2fd73fabe".to_owned(),
+                date,
+            }),
+        };
+        assert_eq!(is_ios_link_broken(&version), broken);
+    }
+
+    #[rstest(
+        input,
+        expected,
+        case("", 0xcbf2_9ce4_8422_2325),
+        case("a", 0xaf63_dc4c_8601_ec8c),
+        case("foobar", 0x8594_4171_f739_67e8)
+    )]
+    fn test_stable_hash(input: &str, expected: u64) {
+        assert_eq!(stable_hash(input.as_bytes()), expected);
+    }
+}